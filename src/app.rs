@@ -1,31 +1,99 @@
-use std::{fs, io};
+use std::{fs, io, time::Duration};
 
 use crate::{
-    editor::{content::EditorContent, events, renderer, state::EditorState, viewport::Viewport},
-    terminal::{self, events::{Event::Key, KeyCode::{Char, Esc}, CTRL}}
+    editor::{content::EditorContent, events, languages, renderer, row::Row, state::{EditorState, ReRenderContent}, viewport::Viewport},
+    file::{config, diff, editorconfig::{self, EditorConfig}, encoding, history::HistoryStore, save, swap::SwapFile, watch::{FileChange, FileWatch}},
+    terminal::{self, events::{Event::Key, KeyCode::{Char, Esc, F}, CTRL}}
 };
 
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+
+struct OpenFile<'a> {
+    name: &'a str,
+    swap: SwapFile,
+    watch: FileWatch,
+    history: HistoryStore,
+    config: EditorConfig,
+    swap_failed: bool,
+}
 
 pub fn start(file_name: &str) -> io::Result<()> {
-    let mut state = create_editor_state(file_name)?;
+    let config = editorconfig::load(file_name).unwrap_or_default();
+    let mut state = create_editor_state(file_name, &config)?;
+    let mut file = OpenFile {
+        name: file_name,
+        swap: SwapFile::new(file_name),
+        watch: FileWatch::new(file_name),
+        history: HistoryStore::new(),
+        config,
+        swap_failed: false,
+    };
+    load_settings(&file, &mut state);
+
+    if state.settings.persist_undo {
+        match file.history.load(file_name, &state.content.to_string()) {
+            Ok(Some(history)) => state.set_history(history),
+            Ok(None) => {},
+            Err(err) => show_message(&mut state, format!("{}\n{}", HISTORY_NOT_LOADED, err)),
+        }
+    } else {
+        let _ = file.history.remove(file_name);
+    }
 
-    terminal::on_alternate_screen(file_name, ||
-        event_loop(file_name, &mut state)
-    )
+    terminal::on_alternate_screen(file_name, || {
+        offer_recovery(&mut file, &mut state)?;
+        event_loop(&mut file, &mut state)?;
+        let _ = file.swap.remove();
+        Ok(())
+    })
 }
 
 
-fn create_editor_state(file_name: &str) -> io::Result<EditorState> {
-    let content = read_content(file_name)?;
+fn create_editor_state(file_name: &str, config: &EditorConfig) -> io::Result<EditorState> {
+    let content = read_content(file_name, config)?;
     let viewport = create_viewport()?;
 
-    Ok(EditorState::new(content, viewport, (0, 0), None))
+    let mut state = EditorState::new(content, viewport, (0, 0), None);
+    state.set_language(languages::language(file_name));
+    Ok(state)
+}
+
+fn read_content(file_name: &str, config: &EditorConfig) -> io::Result<EditorContent> {
+    let mut content = EditorContent::parse(&read_file(file_name, config)?);
+
+    if let (1, Some(end_of_line)) = (content.lines.len(), config.end_of_line) {
+        content.delimiter = end_of_line.to_string();
+    }
+    Ok(content)
+}
+
+fn read_file(file_name: &str, config: &EditorConfig) -> io::Result<String> {
+    encoding::decode(&fs::read(file_name)?, config.charset)
 }
 
-fn read_content(file_name: &str) -> io::Result<EditorContent> {
-    let file_content = fs::read_to_string(file_name)?;
+const HISTORY_NOT_LOADED: &str = "Undo history not loaded:";
+const SETTINGS_WARNING: &str = "Some settings could not be applied:";
 
-    Ok(EditorContent::parse(&file_content))
+fn show_message(state: &mut EditorState, message: String) {
+    state.overlay = Some(match state.overlay.take() {
+        Some(overlay) => format!("{}\n\n{}", overlay, message),
+        None => message,
+    });
+}
+
+fn load_settings(file: &OpenFile, state: &mut EditorState) -> ReRenderContent {
+    let (settings, warnings) = config::load(file.name);
+    state.apply_settings(settings);
+    match file.config.indent {
+        Some(indent) if !state.is_indent_overridden() => state.indent = indent,
+        _ => {}
+    }
+
+    if !warnings.is_empty() {
+        show_message(state, format!("{}\n{}", SETTINGS_WARNING, warnings.join("\n")));
+    }
+    true
 }
 
 fn create_viewport() -> io::Result<Viewport> {
@@ -35,22 +103,162 @@ fn create_viewport() -> io::Result<Viewport> {
 }
 
 
-fn event_loop(file_name: &str, state: &mut EditorState) -> io::Result<()> {
+fn event_loop(file: &mut OpenFile, state: &mut EditorState) -> io::Result<()> {
     let mut rerender_content = true;
     loop {
+        rerender_content |= state.update_highlighting();
         terminal::output(
             renderer::render(&state, rerender_content)
         )?;
 
+        match terminal::poll_event(POLL_INTERVAL)? {
+            Some(Key(Esc, 0)) if state.prompt.is_none() && state.undo_browser.is_none() && state.overlay.is_none() && state.carets().is_empty() => break Ok(()),
+            Some(Key(Char('S'), CTRL)) => rerender_content = save_file(file, state)?,
+            Some(Key(F(5), 0)) if state.prompt.is_none() && state.overlay.is_none() => rerender_content = load_settings(file, state),
+            Some(event) => rerender_content = events::process_event(&event, state),
+            None => rerender_content = false
+        }
+
+        let file_change = file.watch.check_due();
+        rerender_content |= on_file_change(file_change, file, state)?;
+
+        let swapped = file.swap.update(&state.content, state.changes());
+        rerender_content |= report_swap_error(file, state, swapped);
+    }
+}
+
+
+const MODIFIED_QUESTION: &str = "File changed on disk. [R]eload or [O]verwrite? (Esc to ignore)";
+const DELETED_QUESTION: &str = "File deleted from disk. [S]ave it again? (Esc to ignore)";
+
+fn save_file(file: &mut OpenFile, state: &mut EditorState) -> io::Result<ReRenderContent> {
+    match file.watch.check() {
+        FileChange::Modified => match ask(state, MODIFIED_QUESTION, &['R', 'O'])? {
+            Some('R') => reload_file(file, state),
+            Some(_) => clean_up_and_write(file, state),
+            None => Ok(false)
+        },
+        _ => clean_up_and_write(file, state)
+    }
+}
+
+fn clean_up_and_write(file: &mut OpenFile, state: &mut EditorState) -> io::Result<ReRenderContent> {
+    let EditorConfig { trim_trailing_whitespace, insert_final_newline, .. } = file.config;
+    let rerender_content = state.clean_up_whitespace(trim_trailing_whitespace, insert_final_newline);
+    write_file(file, state).map(|rerender| rerender || rerender_content)
+}
+
+fn on_file_change(file_change: FileChange, file: &mut OpenFile, state: &mut EditorState) -> io::Result<ReRenderContent> {
+    match file_change {
+        FileChange::Unchanged => Ok(false),
+        FileChange::Modified => match ask(state, MODIFIED_QUESTION, &['R', 'O'])? {
+            Some('R') => reload_file(file, state),
+            Some(_) => clean_up_and_write(file, state),
+            None => { file.watch.record(); Ok(false) }
+        },
+        FileChange::Deleted => match ask(state, DELETED_QUESTION, &['S'])? {
+            Some(_) => clean_up_and_write(file, state),
+            None => { file.watch.record(); Ok(false) }
+        },
+    }
+}
+
+const NOT_SAVED: &str = "File not saved:";
+const HISTORY_NOT_SAVED: &str = "File saved, but its undo history was not:";
+
+fn write_file(file: &mut OpenFile, state: &mut EditorState) -> io::Result<ReRenderContent> {
+    let content = state.content.to_string();
+    let saved = encoding::encode(&content, file.config.charset)
+        .and_then(|bytes| save::save(file.name, &bytes, &state.settings.backup));
+    if let Err(err) = saved {
+        state.overlay = Some(format!("{}\n{}", NOT_SAVED, err));
+        return Ok(true);
+    }
+    let rerender_content = match state.settings.persist_undo {
+        true => match file.history.save(file.name, &content, state.history()) {
+            Ok(()) => false,
+            Err(err) => { show_message(state, format!("{}\n{}", HISTORY_NOT_SAVED, err)); true },
+        },
+        false => false,
+    };
+    file.watch.record();
+    let swapped = file.swap.saved(state.changes());
+    Ok(report_swap_error(file, state, swapped) || rerender_content)
+}
+
+fn reload_file(file: &mut OpenFile, state: &mut EditorState) -> io::Result<ReRenderContent> {
+    let rerender_content = state.replace_content(&read_file(file.name, &file.config)?);
+    file.watch.record();
+    let swapped = file.swap.saved(state.changes());
+    Ok(report_swap_error(file, state, swapped) || rerender_content)
+}
+
+const SWAP_FAILED: &str = "Swap file error, unsaved changes may not be recoverable after a crash:";
+
+fn report_swap_error(file: &mut OpenFile, state: &mut EditorState, result: io::Result<()>) -> ReRenderContent {
+    match result {
+        Err(err) if !file.swap_failed => {
+            file.swap_failed = true;
+            show_message(state, format!("{}\n{}", SWAP_FAILED, err));
+            true
+        },
+        _ => false
+    }
+}
+
+
+const RECOVERY_QUESTION: &str = "Unsaved changes from a previous session found. [R]ecover, [C]ompare or [D]iscard?";
+const COMPARE_HEADER: &str = "Unsaved changes (- in file, + recovered). Press Esc to go back.";
+
+fn offer_recovery(file: &mut OpenFile, state: &mut EditorState) -> io::Result<()> {
+    let swap_content = match file.swap.recoverable(file.name, &state.content.to_string()) {
+        Ok(Some(swap_content)) => swap_content,
+        Ok(None) => return Ok(()),
+        Err(err) => { report_swap_error(file, state, Err(err)); return Ok(()) }
+    };
+
+    loop {
+        match ask(state, RECOVERY_QUESTION, &['R', 'C', 'D'])? {
+            Some('R') => { state.replace_content(&swap_content); break }
+            Some('C') => compare(state, &swap_content)?,
+            Some(_) => { let removed = file.swap.remove(); report_swap_error(file, state, removed); break }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+fn compare(state: &EditorState, swap_content: &str) -> io::Result<()> {
+    let mut diff_state = diff_state(state, swap_content);
+    loop {
+        terminal::output(renderer::render(&diff_state, true))?;
+
         match terminal::read_event()? {
             Key(Esc, 0) => break Ok(()),
-            Key(Char('S'), CTRL) => save_file(file_name, &state.content)?,
-            event => rerender_content = events::process_event(&event, state)
+            event => { events::process_event(&event, &mut diff_state); }
         }
     }
 }
 
-fn save_file(file_name: &str, content: &EditorContent) -> io::Result<()> {
-    let content = content.to_string();
-    fs::write(file_name, content)
+fn diff_state(state: &EditorState, swap_content: &str) -> EditorState {
+    let swap_content = EditorContent::parse(swap_content);
+    let old: Vec<&str> = state.content.lines.iter().map(|row| &row[..]).collect();
+    let new: Vec<&str> = swap_content.lines.iter().map(|row| &row[..]).collect();
+
+    let mut lines = vec![Row::new(COMPARE_HEADER), Row::new("")];
+    lines.extend(diff::diff(&old, &new).iter().map(|line| Row::new(&line.to_string())));
+
+    let Viewport { width, height, .. } = state.viewport;
+    EditorState::new(EditorContent::new(lines, state.content.delimiter.clone()), Viewport::new(0, 0, width, height), (0, 0), None)
+}
+
+fn ask(state: &EditorState, question: &str, answers: &[char]) -> io::Result<Option<char>> {
+    terminal::output(renderer::render_question(state, question))?;
+    loop {
+        match terminal::read_event()? {
+            Key(Esc, 0) => return Ok(None),
+            Key(Char(c), 0) if answers.contains(&c.to_ascii_uppercase()) => return Ok(Some(c.to_ascii_uppercase())),
+            _ => {}
+        }
+    }
 }
@@ -0,0 +1,111 @@
+use super::{content::EditorContent, pos::PosInDocument};
+
+
+const PAIRS: [(&str, &str); 3] = [("(", ")"), ("[", "]"), ("{", "}")];
+const MAX_ROWS: usize = 1000;
+
+pub fn bracket_next_to<F>(content: &EditorContent, (row, col): PosInDocument, mut is_code: F) -> Option<PosInDocument>
+where
+    F: FnMut(PosInDocument) -> bool,
+{
+    let line = content.lines.get(row)?;
+
+    [Some(col), col.checked_sub(1)].into_iter().flatten()
+        .filter(|&col| col < line.len())
+        .find(|&col| is_bracket(&line[col..col + 1]) && is_code((row, col)))
+        .map(|col| (row, col))
+}
+
+pub fn find_match<F>(content: &EditorContent, (row, col): PosInDocument, mut is_code: F) -> Option<PosInDocument>
+where
+    F: FnMut(PosInDocument) -> bool,
+{
+    let bracket = &content.lines[row][col..col + 1];
+    let mut depth = 0;
+
+    if let Some(&(open, close)) = PAIRS.iter().find(|(open, _)| *open == bracket) {
+        let positions = (row..content.lines.len().min(row + MAX_ROWS))
+            .flat_map(|r| (if r == row { col } else { 0 }..content.lines[r].len()).map(move |c| (r, c)));
+        for pos in positions {
+            depth += step(&content.lines[pos.0][pos.1..pos.1 + 1], open, close, || is_code(pos));
+            if depth == 0 { return Some(pos) }
+        }
+    } else if let Some(&(open, close)) = PAIRS.iter().find(|(_, close)| *close == bracket) {
+        let positions = (row.saturating_sub(MAX_ROWS - 1)..=row).rev()
+            .flat_map(|r| (0..if r == row { col + 1 } else { content.lines[r].len() }).rev().map(move |c| (r, c)));
+        for pos in positions {
+            depth += step(&content.lines[pos.0][pos.1..pos.1 + 1], close, open, || is_code(pos));
+            if depth == 0 { return Some(pos) }
+        }
+    }
+    None
+}
+
+fn is_bracket(grapheme: &str) -> bool {
+    PAIRS.iter().any(|&(open, close)| grapheme == open || grapheme == close)
+}
+
+fn step<F>(grapheme: &str, inner: &str, outer: &str, is_code: F) -> isize
+where
+    F: FnOnce() -> bool,
+{
+    let delta = if grapheme == inner { 1 } else if grapheme == outer { -1 } else { 0 };
+    if delta != 0 && is_code() { delta } else { 0 }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::{editor::row::Row, s, vecr};
+    use super::*;
+
+    fn content(lines: Vec<Row>) -> EditorContent {
+        EditorContent::new(lines, s!["\n"])
+    }
+
+    #[test]
+    fn bracket_after_then_before_cursor() {
+        let content = content(vecr!["a(b)"]);
+        assert_eq!(bracket_next_to(&content, (0, 1), |_| true), Some((0, 1)));
+        assert_eq!(bracket_next_to(&content, (0, 4), |_| true), Some((0, 3)));
+        assert_eq!(bracket_next_to(&content, (0, 0), |_| true), None);
+    }
+
+    #[test]
+    fn match_forward_over_lines() {
+        let content = content(vecr!["fn a() {", "    if b { c[0] }", "}"]);
+        assert_eq!(find_match(&content, (0, 7), |_| true), Some((2, 0)));
+        assert_eq!(find_match(&content, (1, 9), |_| true), Some((1, 16)));
+    }
+
+    #[test]
+    fn match_backward() {
+        let content = content(vecr!["[(", "x)]"]);
+        assert_eq!(find_match(&content, (1, 2), |_| true), Some((0, 0)));
+        assert_eq!(find_match(&content, (1, 1), |_| true), Some((0, 1)));
+    }
+
+    #[test]
+    fn skip_brackets_outside_code() {
+        let content = content(vecr!["f(\")\", x)"]);
+        assert_eq!(find_match(&content, (0, 1), |(_, col)| !(2..5).contains(&col)), Some((0, 8)));
+    }
+
+    #[test]
+    fn match_within_row_window() {
+        let mut lines = vecr!["{"];
+        lines.extend(vec![Row::new(""); MAX_ROWS - 2]);
+        lines.push(Row::new("}"));
+        assert_eq!(find_match(&content(lines.clone()), (0, 0), |_| true), Some((MAX_ROWS - 1, 0)));
+        assert_eq!(find_match(&content(lines.clone()), (MAX_ROWS - 1, 0), |_| true), Some((0, 0)));
+
+        lines.insert(1, Row::new(""));
+        assert_eq!(find_match(&content(lines.clone()), (0, 0), |_| true), None);
+        assert_eq!(find_match(&content(lines), (MAX_ROWS, 0), |_| true), None);
+    }
+
+    #[test]
+    fn unmatched_bracket() {
+        assert_eq!(find_match(&content(vecr!["(()"]), (0, 0), |_| true), None);
+    }
+}
@@ -0,0 +1,90 @@
+use std::cmp::{max, min};
+
+use super::{content::EditorContent, cursor::Cursor, edit::EditOp::{self, Delete, Insert, Replace}, pos::{PosInDocument, PosInDocumentExt}, state::Selection};
+
+
+pub struct Caret {
+    pub cursor: Cursor,
+    pub selection_pos: Option<PosInDocument>,
+}
+
+pub type MonoPos = (usize, usize);
+
+impl Caret {
+    pub fn new(cursor_pos: PosInDocument, selection_pos: Option<PosInDocument>) -> Self {
+        Self { cursor: Cursor::from(cursor_pos), selection_pos }
+    }
+
+    pub fn selection(&self) -> Selection {
+        let cursor_pos = self.cursor.pos();
+        self.selection_pos.map(|pos| if pos.is_before(&cursor_pos) { (pos, cursor_pos) } else { (cursor_pos, pos) })
+    }
+}
+
+
+pub fn column(content: &EditorContent, (anchor_row, anchor_col): MonoPos, (corner_row, corner_col): MonoPos) -> Vec<Caret> {
+    (min(anchor_row, corner_row)..=max(anchor_row, corner_row)).map(|row_idx| {
+        let row = &content.lines[row_idx];
+        let (from, to) = (min(row.char_idx_at(anchor_col), row.len()), min(row.char_idx_at(corner_col), row.len()));
+        Caret::new((row_idx, to), if from == to { None } else { Some((row_idx, from)) })
+    }).collect()
+}
+
+pub fn shift(pos: PosInDocument, edit_op: &EditOp) -> PosInDocument {
+    let deleted_to = edit_op.deleted_to();
+    let inserted_to = match edit_op {
+        Delete { from, .. } => *from,
+        Insert { .. } | Replace { .. } => edit_op.to(),
+    };
+
+    if pos.is_before(&edit_op.from()) {
+        pos
+    } else if pos.is_before(&deleted_to) {
+        inserted_to
+    } else if pos.0 == deleted_to.0 {
+        (inserted_to.0, inserted_to.1 + pos.1 - deleted_to.1)
+    } else {
+        (pos.0 - deleted_to.0 + inserted_to.0, pos.1)
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::{s, vecr};
+    use crate::editor::row::Row;
+    use super::*;
+
+    #[test]
+    fn column_clamps_short_rows() {
+        let content = EditorContent::new(vecr!["Hello", "Hi", "World"], s!["\n"]);
+        let carets = column(&content, (0, 1), (2, 4));
+
+        assert_eq!(
+            carets.iter().map(|caret| (caret.cursor.pos(), caret.selection_pos)).collect::<Vec<_>>(),
+            vec![((0, 4), Some((0, 1))), ((1, 2), Some((1, 1))), ((2, 4), Some((2, 1)))]
+        );
+    }
+
+    #[test]
+    fn shift_after_insert_on_same_row() {
+        let edit_op = EditOp::insert((0, 2), "ab");
+        assert_eq!(shift((0, 5), &edit_op), (0, 7));
+        assert_eq!(shift((0, 1), &edit_op), (0, 1));
+    }
+
+    #[test]
+    fn shift_after_multi_line_delete() {
+        let content = EditorContent::new(vecr!["Hello", "World", "Again"], s!["\n"]);
+        let edit_op = EditOp::delete(&content, (0, 2), (1, 3));
+        assert_eq!(shift((1, 4), &edit_op), (0, 3));
+        assert_eq!(shift((2, 4), &edit_op), (1, 4));
+    }
+
+    #[test]
+    fn shift_after_replace_with_newline() {
+        let content = EditorContent::new(vecr!["Hello World"], s!["\n"]);
+        let edit_op = EditOp::replace(&content, (0, 5), (0, 6), "\n");
+        assert_eq!(shift((0, 8), &edit_op), (1, 2));
+    }
+}
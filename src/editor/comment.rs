@@ -0,0 +1,133 @@
+use super::{indent::first_non_whitespace, row::Row};
+
+
+pub struct LineEdit {
+    pub text: String,
+    at: usize,
+    delta: isize,
+}
+
+impl LineEdit {
+    pub fn shift(&self, col: usize) -> usize {
+        let len = self.delta.unsigned_abs();
+
+        match self.delta >= 0 {
+            true if col >= self.at => col + len,
+            false if col >= self.at + len => col - len,
+            false if col > self.at => self.at,
+            _ => col
+        }
+    }
+}
+
+pub fn toggle_lines(lines: &[Row], token: &str) -> Vec<LineEdit> {
+    let token = token.trim_end();
+    let code: Vec<&Row> = lines.iter().filter(|line| first_non_whitespace(line) < line.len()).collect();
+    let is_commented = !code.is_empty() && code.iter().all(|line| starts_with_token(&line[first_non_whitespace(line)..], token));
+
+    if is_commented {
+        lines.iter().map(|line| uncomment(line, token)).collect()
+    } else {
+        let col = code.iter().map(|line| first_non_whitespace(line)).min().unwrap_or(0);
+        lines.iter().map(|line| comment(line, token, col)).collect()
+    }
+}
+
+fn starts_with_token(text: &str, token: &str) -> bool {
+    if !token.chars().all(|c| c.is_ascii_alphabetic()) {
+        return text.starts_with(token);
+    }
+
+    text.get(..token.len()).is_some_and(|start| start.eq_ignore_ascii_case(token))
+        && !text[token.len()..].starts_with(|c: char| c.is_alphanumeric())
+}
+
+fn comment(line: &Row, token: &str, col: usize) -> LineEdit {
+    if first_non_whitespace(line) == line.len() {
+        return LineEdit { text: line[..].to_string(), at: 0, delta: 0 };
+    }
+
+    let prefix = format!("{} ", token);
+    LineEdit { text: format!("{}{}{}", &line[..col], prefix, &line[col..]), at: col, delta: Row::new(&prefix).len() as isize }
+}
+
+fn uncomment(line: &Row, token: &str) -> LineEdit {
+    let col = first_non_whitespace(line);
+    if col == line.len() {
+        return LineEdit { text: line[..].to_string(), at: 0, delta: 0 };
+    }
+
+    let rest = &line[col..][token.len()..];
+    let rest = rest.strip_prefix(' ').unwrap_or(rest);
+    let removed = Row::new(&line[col..]).len() - Row::new(rest).len();
+    LineEdit { text: format!("{}{}", &line[..col], rest), at: col, delta: -(removed as isize) }
+}
+
+pub fn toggle_block(text: &str, (open, close): (&str, &str)) -> String {
+    match text.strip_prefix(open).and_then(|inner| inner.strip_suffix(close)) {
+        Some(inner) => inner.to_string(),
+        None => format!("{}{}{}", open, text, close),
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::vecr;
+    use super::*;
+
+    fn texts(edits: Vec<LineEdit>) -> Vec<String> {
+        edits.into_iter().map(|edit| edit.text).collect()
+    }
+
+    #[test]
+    fn comment_at_shallowest_indent() {
+        let lines = vecr!["    if a {", "", "        b();", "    }"];
+        assert_eq!(texts(toggle_lines(&lines, "//")), vec!["    // if a {", "", "    //     b();", "    // }"]);
+    }
+
+    #[test]
+    fn uncomment_when_all_commented() {
+        let lines = vecr!["  # a", "  #b", "   "];
+        assert_eq!(texts(toggle_lines(&lines, "#")), vec!["  a", "  b", "   "]);
+    }
+
+    #[test]
+    fn comment_when_partly_commented() {
+        let lines = vecr!["; a", "b"];
+        assert_eq!(texts(toggle_lines(&lines, ";")), vec!["; ; a", "; b"]);
+    }
+
+    #[test]
+    fn token_with_trailing_space() {
+        let (plain, commented) = (vecr!["echo"], vecr!["REM echo"]);
+        assert_eq!(texts(toggle_lines(&plain, "REM ")), vec!["REM echo"]);
+        assert_eq!(texts(toggle_lines(&commented, "REM ")), vec!["echo"]);
+    }
+
+    #[test]
+    fn keyword_token_ignores_case() {
+        let (lower, mixed) = (vecr!["rem echo"], vecr!["Rem echo", "REM"]);
+        assert_eq!(texts(toggle_lines(&lower, "REM ")), vec!["echo"]);
+        assert_eq!(texts(toggle_lines(&mixed, "REM ")), vec!["echo", ""]);
+
+        let words = vecr!["rename a b"];
+        assert_eq!(texts(toggle_lines(&words, "REM ")), vec!["REM rename a b"]);
+    }
+
+    #[test]
+    fn shift_columns() {
+        let (plain, commented) = (vecr!["  foo"], vecr!["  // foo"]);
+        let edit = &toggle_lines(&plain, "//")[0];
+        assert_eq!((edit.shift(1), edit.shift(2), edit.shift(4)), (1, 5, 7));
+
+        let edit = &toggle_lines(&commented, "//")[0];
+        assert_eq!((edit.shift(1), edit.shift(3), edit.shift(6)), (1, 2, 3));
+    }
+
+    #[test]
+    fn toggle_block_comment() {
+        assert_eq!(toggle_block("a\nb", ("/*", "*/")), "/*a\nb*/");
+        assert_eq!(toggle_block("<#a#>", ("<#", "#>")), "a");
+    }
+}
@@ -1,4 +1,4 @@
-use super::{pos::PosInDocument, row::{Row, RowVecExt}};
+use super::{indent::{self, Indent}, pos::PosInDocument, row::{Row, RowVecExt}};
 
 pub const CRLF: &str = "\r\n";
 pub const LF: &str = "\n";
@@ -7,16 +7,18 @@ pub const CR: &str = "\r";
 pub struct EditorContent {
     pub lines: Vec<Row>,
     pub delimiter: String,
+    pub indent: Option<Indent>,
 }
 
 impl EditorContent {
     pub fn new(lines: Vec<Row>, delimiter: String) -> Self {
-        Self { lines, delimiter }
+        Self { lines, delimiter, indent: None }
     }
 
     pub fn parse(content: &str) -> Self {
         let (lines, delimiter) = split(content);
-        Self::new(lines, delimiter)
+        let indent = indent::detect(&lines);
+        Self { lines, delimiter, indent }
     }
 
     pub fn line_end(&self, row: usize) -> PosInDocument {
@@ -94,4 +96,10 @@ mod test {
     test_split! { split_crlf, "Hello\r\nWorld\r\n", vecr!["Hello", "World", ""], CRLF }
     test_split! { split_cr, "Hello\rWonderful\rWorld", vecr!["Hello", "Wonderful", "World"], CR }
     test_split! { split_lf, "Hello\nWorld\n", vecr!["Hello", "World", ""], LF }
+
+    #[test]
+    fn parse_detects_indent() {
+        assert_eq!(EditorContent::parse("if a:\n   b\n").indent, Some(Indent::Spaces(3)));
+        assert_eq!(EditorContent::parse("Hello").indent, None);
+    }
 }
@@ -1,6 +1,6 @@
 use std::cmp::min;
 
-use super::{content::EditorContent, pos::PosInDocument};
+use super::{content::EditorContent, indent, pos::PosInDocument};
 
 
 #[derive(PartialEq, Debug)]
@@ -82,10 +82,37 @@ impl Cursor {
         self.move_to(content, move_to)
     }
 
+    pub fn move_word_left(&self, content: &EditorContent) -> NavigationCommand {
+        if self.col == 0 {
+            return self.move_left(content);
+        }
+
+        let word_start = content.lines[self.row].word_starts().into_iter().rev()
+            .find(|&start| start < self.col)
+            .unwrap_or(0);
+        self.move_to(content, (self.row, word_start))
+    }
+
+    pub fn move_word_right(&self, content: &EditorContent) -> NavigationCommand {
+        if self.col == content.line_len(self.row) {
+            return self.move_right(content);
+        }
+
+        let word_start = content.lines[self.row].word_starts().into_iter()
+            .find(|&start| start > self.col)
+            .unwrap_or(content.line_len(self.row));
+        self.move_to(content, (self.row, word_start))
+    }
+
     pub fn move_line_start(&self, content: &EditorContent) -> NavigationCommand {
         self.move_to(content, (self.row, 0))
     }
 
+    pub fn move_home(&self, content: &EditorContent) -> NavigationCommand {
+        let first = indent::first_non_whitespace(&content.lines[self.row]);
+        self.move_to(content, (self.row, if self.col == first { 0 } else { first }))
+    }
+
     pub fn move_line_end(&self, content: &EditorContent) -> NavigationCommand {
         self.move_to(content, content.line_end(self.row))
     }
@@ -142,4 +169,36 @@ mod test {
         ]);
         assert_eq!(cursor.move_down(&content, 1), Some(curs(2, 5, Some(5))));
     }
+
+    #[test]
+    fn home_toggles_first_non_whitespace() {
+        let content = content(vec!["    Hello"]);
+        assert_eq!(curs(0, 7, None).move_home(&content), Some(curs(0, 4, None)));
+        assert_eq!(curs(0, 4, None).move_home(&content), Some(curs(0, 0, None)));
+        assert_eq!(curs(0, 0, None).move_home(&content), Some(curs(0, 4, None)));
+    }
+
+    #[test]
+    fn word_left_to_word_start() {
+        let content = content(vec!["Hello, kůň world"]);
+        assert_eq!(curs(0, 12, None).move_word_left(&content), Some(curs(0, 11, None)));
+        assert_eq!(curs(0, 11, None).move_word_left(&content), Some(curs(0, 7, None)));
+        assert_eq!(curs(0, 7, None).move_word_left(&content), Some(curs(0, 5, None)));
+    }
+
+    #[test]
+    fn word_left_to_previous_line() {
+        let content = content(vec!["Hello", "  world"]);
+        assert_eq!(curs(1, 2, None).move_word_left(&content), Some(curs(1, 0, None)));
+        assert_eq!(curs(1, 0, None).move_word_left(&content), Some(curs(0, 5, None)));
+    }
+
+    #[test]
+    fn word_right_to_next_word_start() {
+        let content = content(vec!["Hello, kůň world", "next"]);
+        assert_eq!(curs(0, 0, None).move_word_right(&content), Some(curs(0, 5, None)));
+        assert_eq!(curs(0, 5, None).move_word_right(&content), Some(curs(0, 7, None)));
+        assert_eq!(curs(0, 11, None).move_word_right(&content), Some(curs(0, 16, None)));
+        assert_eq!(curs(0, 16, None).move_word_right(&content), Some(curs(1, 0, None)));
+    }
 }
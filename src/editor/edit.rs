@@ -1,7 +1,7 @@
 use super::{content::{split, EditorContent}, edit::EditOp::*, pos::PosInDocument, row::Row};
 
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub enum EditOp {
     Insert { from: PosInDocument, lines: Vec<Row> },
     Delete { from: PosInDocument, lines: Vec<Row> },
@@ -41,25 +41,37 @@ impl EditOp {
         split(str).0
     }
 
+    pub fn from(&self) -> PosInDocument {
+        match self {
+            Insert { from, .. } | Delete { from, .. } | Replace { from, .. } => *from
+        }
+    }
+
     pub fn to(&self) -> PosInDocument {
-        let (from, lines) = match self {
-            Insert { from, lines } => (from, lines),
-            Delete { from, lines } => (from, lines),
-            Replace { from, inserted_lines, .. } => (from, inserted_lines),
-        };
+        match self {
+            Insert { from, lines } | Delete { from, lines } => end(*from, lines),
+            Replace { from, inserted_lines, .. } => end(*from, inserted_lines),
+        }
+    }
 
-        let (from_row, from_col) = from;
+    pub fn deleted_to(&self) -> PosInDocument {
+        match self {
+            Insert { from, .. } => *from,
+            Delete { from, lines } => end(*from, lines),
+            Replace { from, deleted_lines, .. } => end(*from, deleted_lines),
+        }
+    }
+}
 
-        let to_row = from_row + lines.len() - 1;
+fn end((from_row, from_col): PosInDocument, lines: &[Row]) -> PosInDocument {
+    let to_row = from_row + lines.len() - 1;
 
-        let to_col_offset = if lines.len() == 1 { from_col } else { &0 };
-        let to_col = to_col_offset + lines[lines.len() - 1].len();
+    let to_col_offset = if lines.len() == 1 { from_col } else { 0 };
+    let to_col = to_col_offset + lines[lines.len() - 1].len();
 
-        (to_row, to_col)
-    }
+    (to_row, to_col)
 }
 
-
 pub fn process(content: &mut EditorContent, edit_op: &EditOp) -> PosInDocument {
     match edit_op {
         Insert { from: (from_row, from_col), lines } => {
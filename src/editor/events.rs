@@ -1,11 +1,49 @@
-use crate::terminal::events::{Event::{self, *}, KeyCode::*, Mouse::*, MouseButton, MouseEvent::*, CTRL, SHIFT};
+use std::iter;
 
-use super::{clipboard::copy_to_clipboard, content::EditorContent, cursor::Cursor, edit::EditOp, pos::PosInDocument, state::{EditorState, ReRenderContent}, viewport::ScrollCommand};
+use crate::terminal::events::{Event::{self, *}, KeyCode::*, Mouse::*, MouseButton, MouseEvent::*, ALT, CTRL, SHIFT};
+
+use super::{
+    caret::MonoPos, clipboard::copy_to_clipboard, comment, content::EditorContent, cursor::Cursor, edit::EditOp, indent::{self, Indent}, pairs, pos::PosInDocument,
+    prompt::PromptKind, row::{Row, RowVecExt}, state::{EditorState, ReRenderContent, Selection}, viewport::{ScrollCommand, Viewport}
+};
 
 
 pub fn process_event(event: &Event, state: &mut EditorState) -> ReRenderContent {
-    if let Some((cursor, is_selection)) = cursor_command(event, state) {
-        state.move_cursor(cursor, is_selection)
+    if state.overlay.is_some() {
+        state.close_overlay()
+    } else if state.undo_browser.is_some() {
+        undo_browser_command(event, state)
+    } else if let Some(kind) = state.prompt.as_ref().map(|prompt| prompt.kind) {
+        prompt_command(kind, event, state)
+    } else if is_find(event) {
+        state.open_find()
+    } else if is_replace(event) {
+        state.open_replace()
+    } else if let Some(forward) = find_next_command(event) {
+        state.find_next(forward)
+    } else if is_go_to(event) {
+        state.open_go_to()
+    } else if is_transform(event) {
+        state.open_transform()
+    } else if is_indent(event) {
+        state.open_indent()
+    } else if let Some(insert) = shell_prompt_command(event) {
+        state.open_shell_command(insert)
+    } else if let Some(back) = jump_command(event) {
+        if back { state.jump_back() } else { state.jump_forward() }
+    } else if is_jump_to_bracket(event) {
+        state.jump_to_bracket()
+    } else if is_clear_carets(event, state) {
+        state.clear_carets()
+    } else if let Some(below) = add_caret_command(event) {
+        state.add_caret(below)
+    } else if is_add_next_occurrence(event) {
+        state.add_next_occurrence()
+    } else if let Some(corner) = column_command(event, state) {
+        state.select_column(corner)
+    } else if let Some((cursor, is_selection)) = cursor_command(event, state) {
+        let rerender_content = state.move_cursor(cursor, is_selection);
+        move_carets(event, is_selection, state) || rerender_content
     } else if is_select_all(event) {
         state.select_all()
     } else if let Some(scroll_to) = scroll_command(event, state) {
@@ -14,7 +52,22 @@ pub fn process_event(event: &Event, state: &mut EditorState) -> ReRenderContent
         state.undo()
     } else if is_redo(event) {
         state.redo()
-    } else if let Some(edit_op) = edit_command(event, state) {
+    } else if let Some(earlier) = time_travel_command(event) {
+        if earlier { state.travel_earlier() } else { state.travel_later() }
+    } else if is_undo_browser(event) {
+        state.open_undo_browser()
+    } else if is_toggle_overwrite(event) {
+        state.toggle_overwrite()
+    } else if is_step_over(event, state) {
+        state.move_cursor(Cursor::from((state.cursor.pos().0, state.cursor.pos().1 + 1)), false)
+    } else if let Some((edit_op, cursor, selection_pos)) = pair_command(event, state)
+        .or_else(|| comment_command(event, state))
+        .or_else(|| line_command(event, state))
+    {
+        state.edit_and_place(edit_op, cursor, selection_pos)
+    } else if let Some(edit_ops) = caret_edit_commands(event, state) {
+        state.edit_carets(edit_ops)
+    } else if let Some(edit_op) = edit_command(event, state, &state.cursor, state.selection()) {
         state.edit(edit_op)
     } else if let Some(edit_op) = clipboard_command(event, state) {
         state.edit(edit_op)
@@ -24,35 +77,226 @@ pub fn process_event(event: &Event, state: &mut EditorState) -> ReRenderContent
 }
 
 
+fn prompt_command(kind: PromptKind, event: &Event, state: &mut EditorState) -> ReRenderContent {
+    match kind {
+        PromptKind::Find => find_command(event, state, EditorState::close_prompt),
+        PromptKind::Replace => find_command(event, state, close_replace_query),
+        PromptKind::ReplaceWith => replace_with_command(event, state),
+        PromptKind::GoTo => go_to_command(event, state),
+        PromptKind::Transform => transform_command(event, state),
+        PromptKind::Indent => indent_command(event, state),
+        PromptKind::Filter | PromptKind::InsertOutput => shell_command(event, state),
+    }
+}
+
+fn find_command<F>(event: &Event, state: &mut EditorState, on_close: F) -> ReRenderContent
+where
+    F: FnOnce(&mut EditorState, bool) -> ReRenderContent,
+{
+    if let Some(forward) = find_next_command(event) {
+        return state.find_next(forward);
+    }
+
+    match event {
+        Key(Esc, 0) => on_close(state, false),
+        Key(Enter, 0) => on_close(state, true),
+        Key(Char(c), ALT) => match c.to_ascii_uppercase() {
+            'C' => state.toggle_search_option(|options| options.case_sensitive = !options.case_sensitive),
+            'W' => state.toggle_search_option(|options| options.whole_word = !options.whole_word),
+            'R' => state.toggle_search_option(|options| options.regex = !options.regex),
+            _ => false
+        },
+        _ => edit_prompt(event, state, EditorState::search_incremental)
+    }
+}
+
+fn close_replace_query(state: &mut EditorState, accept: bool) -> ReRenderContent {
+    if accept { state.open_replace_with() } else { state.close_prompt(false) }
+}
+
+fn replace_with_command(event: &Event, state: &mut EditorState) -> ReRenderContent {
+    if let Some(forward) = find_next_command(event) {
+        return state.find_next(forward);
+    }
+
+    match event {
+        Key(Esc, 0) => state.close_prompt(true),
+        Key(Enter, 0) => state.replace_next(),
+        Key(Char('a' | 'A'), ALT) => state.replace_all(),
+        _ => edit_prompt(event, state, EditorState::update_replace_with)
+    }
+}
+
+fn go_to_command(event: &Event, state: &mut EditorState) -> ReRenderContent {
+    match event {
+        Key(Esc, 0) => state.close_prompt(false),
+        Key(Enter, 0) => state.go_to(),
+        _ => edit_prompt(event, state, |_| false)
+    }
+}
+
+fn transform_command(event: &Event, state: &mut EditorState) -> ReRenderContent {
+    match event {
+        Key(Esc, 0) => state.close_prompt(false),
+        Key(Enter, 0) => state.transform(),
+        _ => edit_prompt(event, state, |_| false)
+    }
+}
+
+fn indent_command(event: &Event, state: &mut EditorState) -> ReRenderContent {
+    match event {
+        Key(Esc, 0) => state.close_prompt(false),
+        Key(Enter, 0) => state.set_indent(),
+        _ => edit_prompt(event, state, |_| false)
+    }
+}
+
+fn shell_command(event: &Event, state: &mut EditorState) -> ReRenderContent {
+    match event {
+        Key(Esc, 0) => state.close_prompt(false),
+        Key(Enter, 0) => state.run_shell_command(),
+        _ => edit_prompt(event, state, |_| false)
+    }
+}
+
+fn edit_prompt<F>(event: &Event, state: &mut EditorState, on_change: F) -> ReRenderContent
+where
+    F: FnOnce(&mut EditorState) -> ReRenderContent,
+{
+    let Some(prompt) = state.prompt.as_mut() else { return false };
+
+    let text = prompt.text().to_string();
+    prompt.process(event);
+
+    if prompt.text() != text { on_change(state) } else { false }
+}
+
+
+fn is_find(event: &Event) -> bool {
+    matches!(event, Key(Char('F'), CTRL))
+}
+
+fn is_replace(event: &Event) -> bool {
+    matches!(event, Key(Char('H'), CTRL))
+}
+
+fn is_go_to(event: &Event) -> bool {
+    matches!(event, Key(Char('G'), CTRL))
+}
+
+fn is_transform(event: &Event) -> bool {
+    matches!(event, Key(Char('T'), CTRL))
+}
+
+fn is_indent(event: &Event) -> bool {
+    matches!(event, Key(Char('i' | 'I'), ALT))
+}
+
+fn shell_prompt_command(event: &Event) -> Option<bool> {
+    match event {
+        Key(Char('E'), CTRL) => Some(false),
+        Key(Char('e' | 'E'), ALT) => Some(true),
+        _ => None
+    }
+}
+
+fn is_jump_to_bracket(event: &Event) -> bool {
+    matches!(event, Key(Char(']'), CTRL))
+}
+
+fn jump_command(event: &Event) -> Option<bool> {
+    match event {
+        Key(Left, ALT) => Some(true),
+        Key(Right, ALT) => Some(false),
+        _ => None
+    }
+}
+
+fn find_next_command(event: &Event) -> Option<bool> {
+    match event {
+        Key(F(3), 0) => Some(true),
+        Key(F(3), SHIFT) => Some(false),
+        _ => None
+    }
+}
+
+
 type CursorCommand = Option<(Cursor, bool)>;
 
 fn cursor_command(event: &Event, state: &EditorState) -> CursorCommand {
-    let EditorState { ref cursor, ref content, ref viewport, .. } = state;
-
     let cursor_command = match *event {
-        Key(ref key, modifiers) => match (key, modifiers) {
-            (Home, 0 | SHIFT) => cursor.move_line_start(content),
-            (End, 0 | SHIFT) => cursor.move_line_end(content),
-            (Up, 0 | SHIFT) => cursor.move_up(content, 1),
-            (Down, 0 | SHIFT) => cursor.move_down(content, 1),
-            (Right, 0 | SHIFT) => cursor.move_right(content),
-            (Left, 0 | SHIFT) => cursor.move_left(content),
-            (PageDown, 0 | SHIFT) => cursor.move_down(content, viewport.height as usize - 1),
-            (PageUp, 0 | SHIFT) => cursor.move_up(content, viewport.height as usize - 1),
+        Mouse(Button(MouseButton::Left, Press | Drag, column, row), _) =>
+            state.cursor.move_to(&state.content, state.viewport.to_absolute((row, column))),
+        _ => move_cursor(event, &state.cursor, &state.content, &state.viewport)
+    };
+
+    cursor_command.map(|cursor| (
+        cursor,
+        matches!(event, Key(_, modifiers) if modifiers & SHIFT != 0) || matches!(event, Mouse(Button(_, Drag, _, _), _))
+    ))
+}
+
+fn move_cursor(event: &Event, cursor: &Cursor, content: &EditorContent, viewport: &Viewport) -> Option<Cursor> {
+    match *event {
+        Key(ref key, modifiers) => match (key, modifiers & !SHIFT) {
+            (Home, 0) => cursor.move_home(content),
+            (End, 0) => cursor.move_line_end(content),
+            (Up, 0) => cursor.move_up(content, 1),
+            (Down, 0) => cursor.move_down(content, 1),
+            (Right, 0) => cursor.move_right(content),
+            (Left, 0) => cursor.move_left(content),
+            (PageDown, 0) => cursor.move_down(content, viewport.height as usize - 1),
+            (PageUp, 0) => cursor.move_up(content, viewport.height as usize - 1),
 
             (Home, CTRL) => cursor.move_document_start(content),
             (End, CTRL) => cursor.move_document_end(content),
+            (Right, CTRL) => cursor.move_word_right(content),
+            (Left, CTRL) => cursor.move_word_left(content),
 
             _ => None
         },
-        Mouse(Button(MouseButton::Left, Press | Drag, column, row)) => cursor.move_to(content, viewport.to_absolute((row, column))),
         _ => None
-    };
+    }
+}
 
-    cursor_command.map(|cursor| (
-        cursor,
-        matches!(event, Key(_, SHIFT)) || matches!(event, Mouse(Button(_, Drag, _, _)))
-    ))
+fn move_carets(event: &Event, is_selection: bool, state: &mut EditorState) -> ReRenderContent {
+    match event {
+        Key(_, _) => state.move_carets(is_selection, |cursor, content, viewport| move_cursor(event, cursor, content, viewport)),
+        _ => state.clear_carets()
+    }
+}
+
+
+fn is_clear_carets(event: &Event, state: &EditorState) -> bool {
+    matches!(event, Key(Esc, 0)) && !state.carets().is_empty()
+}
+
+fn add_caret_command(event: &Event) -> Option<bool> {
+    match event {
+        Key(Up, modifiers) if *modifiers == CTRL | ALT => Some(false),
+        Key(Down, modifiers) if *modifiers == CTRL | ALT => Some(true),
+        _ => None
+    }
+}
+
+fn is_add_next_occurrence(event: &Event) -> bool {
+    matches!(event, Key(Char('n' | 'N'), ALT))
+}
+
+fn column_command(event: &Event, state: &EditorState) -> Option<MonoPos> {
+    let (row, col) = state.column_corner();
+
+    match *event {
+        Key(ref key, modifiers) if modifiers == ALT | SHIFT => match key {
+            Up => row.checked_sub(1).map(|row| (row, col)),
+            Down if row < state.content.last_line_row() => Some((row + 1, col)),
+            Left => col.checked_sub(1).map(|col| (row, col)),
+            Right => Some((row, col + 1)),
+            _ => None
+        },
+        Mouse(Button(MouseButton::Left, Drag, column, row), ALT) => Some(state.viewport.to_absolute((row, column))),
+        _ => None
+    }
 }
 
 
@@ -85,8 +329,8 @@ fn scroll_command(event: &Event, state: &EditorState) -> ScrollCommand {
     let EditorState { ref content, ref viewport, .. } = state;
 
     match event {
-        Key(Up, CTRL) | Mouse(WheelUp(_, _)) => viewport.scroll_up(1),
-        Key(Down, CTRL) | Mouse(WheelDown(_, _)) => viewport.scroll_down(1, content.last_line_row()),
+        Key(Up, CTRL) | Mouse(WheelUp(_, _), _) => viewport.scroll_up(1),
+        Key(Down, CTRL) | Mouse(WheelDown(_, _), _) => viewport.scroll_down(1, content.last_line_row()),
         _ => None
     }
 }
@@ -100,31 +344,266 @@ fn is_redo(event: &Event) -> bool {
     matches!(event, Key(Char('Y'), CTRL))
 }
 
+fn time_travel_command(event: &Event) -> Option<bool> {
+    match event {
+        Key(Char('Z'), modifiers) if *modifiers == CTRL | ALT => Some(true),
+        Key(Char('Y'), modifiers) if *modifiers == CTRL | ALT => Some(false),
+        _ => None
+    }
+}
+
+fn is_undo_browser(event: &Event) -> bool {
+    matches!(event, Key(Char('u' | 'U'), ALT))
+}
+
+fn is_toggle_overwrite(event: &Event) -> bool {
+    matches!(event, Key(Insert, 0))
+}
+
+fn undo_browser_command(event: &Event, state: &mut EditorState) -> ReRenderContent {
+    match event {
+        Key(Up, 0) => state.move_undo_browser(1),
+        Key(Down, 0) => state.move_undo_browser(-1),
+        Key(PageUp, 0) => state.move_undo_browser(10),
+        Key(PageDown, 0) => state.move_undo_browser(-10),
+        Key(Enter, 0) => state.close_undo_browser(true),
+        Key(Esc, 0) => state.close_undo_browser(false),
+        _ => false
+    }
+}
+
 
 type EditCommand = Option<EditOp>;
 
-fn edit_command(event: &Event, state: &EditorState) -> EditCommand {
-    let EditorState { ref content, ref cursor, .. } = state;
-    let selection = state.selection();
+fn is_step_over(event: &Event, state: &EditorState) -> bool {
+    let (row, col) = state.cursor.pos();
+
+    match event {
+        Key(Char(c), 0) if is_pairing(state) && state.selection().is_none() => pairs::is_step_over(&state.content.lines[row], col, *c),
+        _ => false
+    }
+}
+
+type PlacedEditCommand = Option<(EditOp, PosInDocument, Option<PosInDocument>)>;
+
+fn pair_command(event: &Event, state: &EditorState) -> PlacedEditCommand {
+    let content = &state.content;
+    let (row, col) = state.cursor.pos();
+
+    if !is_pairing(state) {
+        return None;
+    }
+
+    match (event, state.selection()) {
+        (Key(Char(c), 0), Some((from, to))) => pairs::closing(*c).map(|close| {
+            let text = format!("{}{}{}", c, content.selected_text(from, to), close);
+            let shift = |(r, col): PosInDocument| if r == from.0 { (r, col + 1) } else { (r, col) };
+            let (cursor, anchor) = if state.cursor.pos() == from { (shift(from), shift(to)) } else { (shift(to), shift(from)) };
+            (EditOp::replace(content, from, to, &text), cursor, Some(anchor))
+        }),
+        (Key(Char(c), 0), None) if pairs::should_close(&content.lines[row], col, *c) => pairs::closing(*c)
+            .map(|close| (EditOp::insert((row, col), &format!("{}{}", c, close)), (row, col + 1), None)),
+        (Key(Backspace, 0), None) if pairs::is_empty_pair(&content.lines[row], col) =>
+            Some((EditOp::delete(content, (row, col - 1), (row, col + 1)), (row, col - 1), None)),
+        _ => None
+    }
+}
+
+fn comment_command(event: &Event, state: &EditorState) -> PlacedEditCommand {
+    let language = state.language()?;
+
+    match event {
+        Key(Char('_' | '/'), CTRL) => language.line_comments.first().and_then(|token| toggle_line_comments(state, token)),
+        Key(Char('/'), ALT) => language.block_comments.first().and_then(|&block| toggle_block_comment(state, block)),
+        Key(Char('/' | '?'), modifiers) if *modifiers == CTRL | SHIFT =>
+            language.block_comments.first().and_then(|&block| toggle_block_comment(state, block)),
+        _ => None
+    }
+}
+
+fn toggle_line_comments(state: &EditorState, token: &str) -> PlacedEditCommand {
+    let content = &state.content;
+    let (cursor, anchor) = (state.cursor.pos(), anchor(state));
+    let (first, last) = selected_rows(state);
+
+    let edits = comment::toggle_lines(&content.lines[first..=last], token);
+    let text = edits.iter().map(|edit| &edit.text[..]).collect::<Vec<_>>().join("\n");
+    let shift = |(row, col): PosInDocument| match row {
+        row if row >= first && row <= last => (row, edits[row - first].shift(col)),
+        _ => (row, col)
+    };
+
+    let edit_op = EditOp::replace(content, (first, 0), content.line_end(last), &text);
+    Some((edit_op, shift(cursor), anchor.map(shift)))
+}
+
+fn toggle_block_comment(state: &EditorState, block: (&str, &str)) -> PlacedEditCommand {
+    let (from, to) = state.selection()?;
+    let text = comment::toggle_block(&state.content.selected_text(from, to), block);
+
+    let edit_op = EditOp::replace(&state.content, from, to, &text);
+    let end = edit_op.to();
+    if state.cursor.pos() == from { Some((edit_op, from, Some(end))) } else { Some((edit_op, end, Some(from))) }
+}
+
+fn line_command(event: &Event, state: &EditorState) -> PlacedEditCommand {
+    match event {
+        Key(Char('D'), CTRL) => duplicate_lines(state),
+        Key(Up, ALT) => move_lines(state, true),
+        Key(Down, ALT) => move_lines(state, false),
+        Key(Char('K'), modifiers) if *modifiers == CTRL | SHIFT => delete_lines(state),
+        Key(Char('J'), CTRL) => join_lines(state),
+        Key(Enter, CTRL) => insert_line(state, false),
+        Key(Enter, modifiers) if *modifiers == CTRL | SHIFT => insert_line(state, true),
+        _ => None
+    }
+}
+
+fn selected_rows(state: &EditorState) -> (usize, usize) {
+    let cursor = state.cursor.pos();
+    let (from, to) = state.selection().unwrap_or((cursor, cursor));
+
+    (from.0, if to.0 > from.0 && to.1 == 0 { to.0 - 1 } else { to.0 })
+}
+
+fn anchor(state: &EditorState) -> Option<PosInDocument> {
+    state.selection().map(|(from, to)| if state.cursor.pos() == from { to } else { from })
+}
+
+fn lines_text(content: &EditorContent, first: usize, last: usize) -> String {
+    content.selection((first, 0), content.line_end(last)).join("\n")
+}
+
+fn duplicate_lines(state: &EditorState) -> PlacedEditCommand {
+    let (first, last) = selected_rows(state);
+    let down = |(row, col): PosInDocument| (row + last - first + 1, col);
+
+    let edit_op = EditOp::insert((first, 0), &format!("{}\n", lines_text(&state.content, first, last)));
+    Some((edit_op, down(state.cursor.pos()), anchor(state).map(down)))
+}
+
+fn move_lines(state: &EditorState, up: bool) -> PlacedEditCommand {
+    let content = &state.content;
+    let (first, last) = selected_rows(state);
+
+    let edit_op = match up {
+        true if first > 0 => EditOp::replace(content, (first - 1, 0), content.line_end(last),
+            &format!("{}\n{}", lines_text(content, first, last), &content.lines[first - 1][..])),
+        false if last < content.last_line_row() => EditOp::replace(content, (first, 0), content.line_end(last + 1),
+            &format!("{}\n{}", &content.lines[last + 1][..], lines_text(content, first, last))),
+        _ => return None
+    };
+    let shift = |(row, col): PosInDocument| match up {
+        true => (row - 1, col),
+        false if row == content.last_line_row() => (row, content.line_len(last)),
+        false => (row + 1, col),
+    };
+    Some((edit_op, shift(state.cursor.pos()), anchor(state).map(shift)))
+}
+
+fn delete_lines(state: &EditorState) -> PlacedEditCommand {
+    let content = &state.content;
+    let (first, last) = selected_rows(state);
+    let col = state.cursor.pos().1;
+
+    if last < content.last_line_row() {
+        let edit_op = EditOp::delete(content, (first, 0), (last + 1, 0));
+        Some((edit_op, (first, col.min(content.line_len(last + 1))), None))
+    } else if first > 0 {
+        let edit_op = EditOp::delete(content, content.line_end(first - 1), content.line_end(last));
+        Some((edit_op, (first - 1, col.min(content.line_len(first - 1))), None))
+    } else {
+        Some((EditOp::delete(content, (0, 0), content.line_end(last)), (0, 0), None))
+    }
+}
+
+fn join_lines(state: &EditorState) -> PlacedEditCommand {
+    let content = &state.content;
+    let (first, last) = selected_rows(state);
+    let last = if last == first { first + 1 } else { last };
+    if last > content.last_line_row() {
+        return None;
+    }
+
+    let mut joined = content.lines[first][..].trim_end().to_string();
+    let mut starts = vec![(0, 0)];
+    for line in &content.lines[first + 1..=last] {
+        let trimmed = line[..].trim_start();
+        if !joined.is_empty() && !trimmed.is_empty() {
+            joined.push(' ');
+        }
+        starts.push((Row::new(&joined).len(), line.len() - Row::new(trimmed).len()));
+        joined.push_str(trimmed.trim_end());
+    }
+
+    let len = Row::new(&joined).len();
+    let shift = |(row, col): PosInDocument| match starts.get(row - first) {
+        Some(&(start, skipped)) => (first, (start + col.saturating_sub(skipped)).min(len)),
+        None => (first, len)
+    };
+    let cursor = match anchor(state) {
+        Some(_) => shift(state.cursor.pos()),
+        None => (first, starts[1].0),
+    };
+
+    let edit_op = EditOp::replace(content, (first, 0), content.line_end(last), &joined);
+    Some((edit_op, cursor, anchor(state).map(shift)))
+}
+
+fn insert_line(state: &EditorState, above: bool) -> PlacedEditCommand {
+    let content = &state.content;
+    let (first, last) = selected_rows(state);
+    let row = if above { first } else { last };
+    let leading = &content.lines[row][..indent::first_non_whitespace(&content.lines[row])];
+    let col = Row::new(leading).len();
+
+    match above {
+        true => Some((EditOp::insert((row, 0), &format!("{}\n", leading)), (row, col), None)),
+        false => Some((EditOp::insert(content.line_end(row), &format!("\n{}", leading)), (row + 1, col), None)),
+    }
+}
+
+fn is_pairing(state: &EditorState) -> bool {
+    state.carets().is_empty() && !state.is_overwrite && state.settings.auto_close_pairs
+}
+
+fn caret_edit_commands(event: &Event, state: &EditorState) -> Option<Vec<EditCommand>> {
+    if state.carets().is_empty() {
+        return None;
+    }
+
+    let edit_ops: Vec<EditCommand> = iter::once(edit_command(event, state, &state.cursor, state.selection()))
+        .chain(state.carets().iter().map(|caret| edit_command(event, state, &caret.cursor, caret.selection())))
+        .collect();
+
+    if edit_ops.iter().any(Option::is_some) { Some(edit_ops) } else { None }
+}
+
+fn edit_command(event: &Event, state: &EditorState, cursor: &Cursor, selection: Selection) -> EditCommand {
+    let content = &state.content;
 
     match selection {
         Some((from, to)) => match event {
-            Key(ref key, modifiers) => match (key, modifiers) {
+            Key(ref key, modifiers) => match (key, *modifiers) {
                 (Char(c), 0) => replace(content, from, to, &c.to_string()),
-                (Enter, 0) => replace(content, from, to, "\n"),
-                (Backspace, 0) => delete(from, to, content),
-                (Delete, 0) => delete(from, to, content),
+                (Enter, 0) => replace(content, from, to, &line_break(content, from, state)),
+                (Backspace, 0 | CTRL | ALT) => delete(from, to, content),
+                (Delete, 0 | CTRL) => delete(from, to, content),
                 _ => None
             },
             Paste(s) => replace(content, from, to, &s),
             _ => None
         },
         None => match event {
-            Key(ref key, modifiers) => match (key, modifiers) {
+            Key(ref key, modifiers) => match (key, *modifiers) {
+                (Char(c), 0) if state.is_overwrite => overwrite_char(cursor, content, *c),
                 (Char(c), 0) => insert_char(cursor, *c),
-                (Enter, 0) => insert_char(cursor, '\n'),
-                (Backspace, 0) => backspace(cursor, content),
+                (Enter, 0) => insert(cursor, &line_break(content, cursor.pos(), state)),
+                (Tab, 0) => tab(cursor, content, state.indent),
+                (Backspace, 0) => outdent(cursor, content, state.indent).or_else(|| backspace(cursor, content)),
                 (Delete, 0) => delete_char(cursor, content),
+                (Backspace, CTRL | ALT) => delete_word_left(cursor, content),
+                (Delete, CTRL) => delete_word_right(cursor, content),
                 _ => None
             },
             Paste(s) => insert(cursor, &s),
@@ -138,6 +617,33 @@ fn insert_char(cursor: &Cursor, c: char) -> EditCommand {
     insert(cursor, &c.to_string())
 }
 
+fn overwrite_char(cursor: &Cursor, content: &EditorContent, c: char) -> EditCommand {
+    let (row, col) = cursor.pos();
+
+    if col < content.line_len(row) {
+        replace(content, (row, col), (row, col + 1), &c.to_string())
+    } else {
+        insert_char(cursor, c)
+    }
+}
+
+fn line_break(content: &EditorContent, (row, col): PosInDocument, state: &EditorState) -> String {
+    match state.settings.auto_indent {
+        true => indent::new_line(&content.lines[row], col, state.indent),
+        false => String::from("\n"),
+    }
+}
+
+fn tab(cursor: &Cursor, content: &EditorContent, indent: Indent) -> EditCommand {
+    let (row, col) = cursor.pos();
+    insert(cursor, &indent::tab(&content.lines[row], col, indent))
+}
+
+fn outdent(cursor: &Cursor, content: &EditorContent, indent: Indent) -> EditCommand {
+    let (row, col) = cursor.pos();
+    indent::outdent_from(&content.lines[row], col, indent).and_then(|from| delete((row, from), (row, col), content))
+}
+
 fn delete_char(cursor: &Cursor, content: &EditorContent) -> EditCommand {
     cursor.move_right(content).and_then(|cursor_right| {
         delete(cursor.pos(), cursor_right.pos(), content)
@@ -150,6 +656,18 @@ fn backspace(cursor: &Cursor, content: &EditorContent) -> EditCommand {
     })
 }
 
+fn delete_word_left(cursor: &Cursor, content: &EditorContent) -> EditCommand {
+    cursor.move_word_left(content).and_then(|word_start| {
+        delete(word_start.pos(), cursor.pos(), content)
+    })
+}
+
+fn delete_word_right(cursor: &Cursor, content: &EditorContent) -> EditCommand {
+    cursor.move_word_right(content).and_then(|word_end| {
+        delete(cursor.pos(), word_end.pos(), content)
+    })
+}
+
 fn insert(cursor: &Cursor, str: &str) -> EditCommand {
     Some(EditOp::insert(cursor.pos(), str))
 }
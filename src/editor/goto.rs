@@ -0,0 +1,74 @@
+use super::pos::PosInDocument;
+
+
+pub fn parse_target(input: &str, (row, _): PosInDocument, line_count: usize) -> Option<PosInDocument> {
+    let input = input.trim();
+
+    if let Some(percent) = input.strip_prefix('%') {
+        let percent = percent.parse::<usize>().ok()?.min(100);
+        return Some(((line_count * percent / 100).min(line_count - 1), 0));
+    }
+
+    let (line, column) = match input.split_once(':') {
+        Some((line, column)) => (line, Some(column)),
+        None => (input, None)
+    };
+
+    let target_row = if let Some(offset) = line.strip_prefix('+') {
+        row + number(offset)?
+    } else if let Some(offset) = line.strip_prefix('-') {
+        row.saturating_sub(number(offset)?)
+    } else {
+        number(line)?.saturating_sub(1)
+    };
+
+    let target_col = match column {
+        Some(column) => number(column)?.saturating_sub(1),
+        None => 0
+    };
+
+    Some((target_row, target_col))
+}
+
+fn number(str: &str) -> Option<usize> {
+    str.trim().parse().ok()
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn line() {
+        assert_eq!(parse_target("120", (5, 3), 200), Some((119, 0)));
+    }
+
+    #[test]
+    fn line_and_column() {
+        assert_eq!(parse_target("120:15", (5, 3), 200), Some((119, 14)));
+    }
+
+    #[test]
+    fn relative_forward() {
+        assert_eq!(parse_target("+30", (5, 3), 200), Some((35, 0)));
+    }
+
+    #[test]
+    fn relative_backward_stops_at_start() {
+        assert_eq!(parse_target("-10", (5, 3), 200), Some((0, 0)));
+    }
+
+    #[test]
+    fn percent() {
+        assert_eq!(parse_target("%50", (5, 3), 200), Some((100, 0)));
+        assert_eq!(parse_target("%100", (5, 3), 200), Some((199, 0)));
+    }
+
+    #[test]
+    fn invalid() {
+        assert_eq!(parse_target("abc", (5, 3), 200), None);
+        assert_eq!(parse_target("12:", (5, 3), 200), None);
+        assert_eq!(parse_target("%", (5, 3), 200), None);
+    }
+}
@@ -0,0 +1,368 @@
+use std::cmp::min;
+
+use super::{content::EditorContent, languages::Language, pos::PosInDocument, row::Row};
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TokenKind {
+    Comment,
+    Keyword,
+    Type,
+    Literal,
+    Number,
+    String,
+    Key,
+    Heading,
+    Variable,
+}
+
+pub type Token = (usize, usize, TokenKind);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LineState {
+    Normal,
+    Comment(usize),
+    String(usize),
+}
+
+pub struct Highlighter {
+    language: Option<&'static Language>,
+    states: Vec<LineState>,
+    tokens: Vec<Vec<Token>>,
+}
+
+impl Highlighter {
+    pub fn new(language: Option<&'static Language>) -> Self {
+        Self { language, states: Vec::new(), tokens: Vec::new() }
+    }
+
+    pub fn language(&self) -> Option<&'static Language> {
+        self.language
+    }
+
+    pub fn invalidate(&mut self, row: usize) {
+        self.states.truncate(row);
+        self.tokens.truncate(row);
+    }
+
+    pub fn update(&mut self, content: &EditorContent, until_row: usize) {
+        let Some(language) = self.language else { return };
+
+        for row in self.tokens.len()..min(until_row, content.lines.len()) {
+            let state = if row == 0 { LineState::Normal } else { self.states[row - 1] };
+            let (tokens, state) = tokenize(language, &content.lines[row], state);
+            self.tokens.push(tokens);
+            self.states.push(state);
+        }
+    }
+
+    pub fn tokens(&self, row: usize) -> &[Token] {
+        self.tokens.get(row).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn kind_at(&mut self, content: &EditorContent, (row, col): PosInDocument) -> Option<TokenKind> {
+        self.update(content, row + 1);
+        self.tokens(row).iter()
+            .find(|&&(from, to, _)| from <= col && col < to)
+            .map(|&(_, _, kind)| kind)
+    }
+}
+
+
+pub fn tokenize(language: &Language, row: &Row, state: LineState) -> (Vec<Token>, LineState) {
+    let (tokens, state) = tokenize_str(language, &row[..], state);
+    let tokens = tokens.into_iter()
+        .map(|(from, to, kind)| (row.char_idx_of_byte(from), row.char_idx_of_byte(to), kind))
+        .collect();
+    (tokens, state)
+}
+
+fn tokenize_str(language: &Language, line: &str, mut state: LineState) -> (Vec<Token>, LineState) {
+    let mut tokens = Vec::new();
+    let first = line.len() - line.trim_start().len();
+    let mut i = 0;
+
+    while i < line.len() {
+        let rest = &line[i..];
+
+        match state {
+            LineState::Comment(idx) => {
+                let (_, close) = language.block_comments[idx];
+                let end = rest.find(close).map(|j| i + j + close.len());
+                push(&mut tokens, (i, end.unwrap_or(line.len()), TokenKind::Comment));
+                i = end.unwrap_or(line.len());
+                if end.is_some() { state = LineState::Normal }
+            },
+            LineState::String(idx) => {
+                let rule = &language.strings[idx];
+                let end = find_close(line, i, rule.close, rule.escape);
+                push(&mut tokens, (i, end.unwrap_or(line.len()), TokenKind::String));
+                i = end.unwrap_or(line.len());
+                if end.is_some() { state = LineState::Normal }
+            },
+            LineState::Normal => {
+                if language.line_comments.iter().any(|prefix| starts_with(language, rest, prefix)) {
+                    push(&mut tokens, (i, line.len(), TokenKind::Comment));
+                    break;
+                }
+
+                if i == first {
+                    if let Some(&(_, kind)) = language.line_starts.iter().find(|(prefix, _)| rest.starts_with(prefix)) {
+                        push(&mut tokens, (i, line.len(), kind));
+                        break;
+                    }
+                    if let Some(end) = key_end(language, rest) {
+                        push(&mut tokens, (i, i + end, TokenKind::Key));
+                        i += end;
+                        continue;
+                    }
+                }
+
+                if let Some(idx) = language.block_comments.iter().position(|(open, _)| rest.starts_with(open)) {
+                    let open = language.block_comments[idx].0;
+                    push(&mut tokens, (i, i + open.len(), TokenKind::Comment));
+                    state = LineState::Comment(idx);
+                    i += open.len();
+                    continue;
+                }
+
+                if let Some(idx) = language.strings.iter().position(|rule| rest.starts_with(rule.open)) {
+                    let open = language.strings[idx].open;
+                    push(&mut tokens, (i, i + open.len(), TokenKind::String));
+                    state = LineState::String(idx);
+                    i += open.len();
+                    continue;
+                }
+
+                if let Some(end) = char_literal_end(language, rest).or_else(|| variable_end(language, rest)) {
+                    let kind = if rest.starts_with('\'') { TokenKind::String } else { TokenKind::Variable };
+                    push(&mut tokens, (i, i + end, kind));
+                    i += end;
+                    continue;
+                }
+
+                let c = rest.chars().next().unwrap_or_default();
+                if c.is_ascii_digit() {
+                    let end = number_end(rest);
+                    push(&mut tokens, (i, i + end, TokenKind::Number));
+                    i += end;
+                } else if c.is_alphabetic() || c == '_' {
+                    let end = word_end(language, rest);
+                    if let Some(kind) = classify(language, &rest[..end]) {
+                        push(&mut tokens, (i, i + end, kind));
+                    }
+                    i += end;
+                } else {
+                    i += c.len_utf8();
+                }
+            },
+        }
+    }
+
+    if let LineState::String(idx) = state {
+        if !language.strings[idx].multiline {
+            state = LineState::Normal;
+        }
+    }
+    (tokens, state)
+}
+
+fn push(tokens: &mut Vec<Token>, token: Token) {
+    match tokens.last_mut() {
+        Some((_, to, kind)) if *to == token.0 && *kind == token.2 => *to = token.1,
+        _ => tokens.push(token)
+    }
+}
+
+fn starts_with(language: &Language, text: &str, prefix: &str) -> bool {
+    match language.case_sensitive {
+        true => text.starts_with(prefix),
+        false => text.get(..prefix.len()).is_some_and(|start| start.eq_ignore_ascii_case(prefix)),
+    }
+}
+
+fn find_close(line: &str, from: usize, close: &str, escape: Option<char>) -> Option<usize> {
+    let mut chars = line[from..].char_indices();
+
+    while let Some((j, c)) = chars.next() {
+        if Some(c) == escape {
+            chars.next();
+        } else if line[from + j..].starts_with(close) {
+            return Some(from + j + close.len());
+        }
+    }
+    None
+}
+
+fn key_end(language: &Language, rest: &str) -> Option<usize> {
+    let key_len = match language.strings.iter().find(|rule| rest.starts_with(rule.open)) {
+        Some(rule) => find_close(rest, rule.open.len(), rule.close, rule.escape)?,
+        None => rest.find(|c| language.key_separators.contains(&c)).map(|separator| rest[..separator].trim_end().len())?,
+    };
+    let is_key = key_len > 0 && rest[key_len..].trim_start().starts_with(|c| language.key_separators.contains(&c));
+    is_key.then_some(key_len)
+}
+
+fn char_literal_end(language: &Language, rest: &str) -> Option<usize> {
+    if !language.char_literals || !rest.starts_with('\'') {
+        return None;
+    }
+
+    match rest[1..].chars().next()? {
+        '\\' => rest.get(3..)?.char_indices().take(10).find(|&(_, c)| c == '\'').map(|(j, _)| j + 4),
+        c => rest[1 + c.len_utf8()..].starts_with('\'').then_some(c.len_utf8() + 2),
+    }
+}
+
+fn variable_end(language: &Language, rest: &str) -> Option<usize> {
+    language.variables.iter().find_map(|(open, close)| {
+        let name = rest.strip_prefix(open)?;
+        let name_len = word_end(language, name);
+        if name_len == 0 {
+            None
+        } else if close.is_empty() {
+            Some(open.len() + name_len)
+        } else {
+            name[name_len..].starts_with(close).then_some(open.len() + name_len + close.len())
+        }
+    })
+}
+
+fn number_end(rest: &str) -> usize {
+    let bytes = rest.as_bytes();
+    (1..bytes.len())
+        .find(|&j| !(bytes[j].is_ascii_alphanumeric() || bytes[j] == b'_' || bytes[j] == b'.' && bytes.get(j + 1).is_some_and(u8::is_ascii_digit)))
+        .unwrap_or(bytes.len())
+}
+
+fn word_end(language: &Language, rest: &str) -> usize {
+    rest.char_indices()
+        .find(|&(_, c)| !(c.is_alphanumeric() || c == '_' || language.word_chars.contains(&c)))
+        .map(|(j, _)| j)
+        .unwrap_or(rest.len())
+}
+
+fn classify(language: &Language, word: &str) -> Option<TokenKind> {
+    let is_in = |words: &[&str]| words.iter().any(|w| if language.case_sensitive { *w == word } else { w.eq_ignore_ascii_case(word) });
+
+    if is_in(language.keywords) {
+        Some(TokenKind::Keyword)
+    } else if is_in(language.types) || language.capitalized_types && word.starts_with(char::is_uppercase) {
+        Some(TokenKind::Type)
+    } else if is_in(language.literals) {
+        Some(TokenKind::Literal)
+    } else {
+        None
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::{s, vecr};
+    use crate::editor::languages::{language, BATCH, JSON, MARKDOWN, POWERSHELL, RUST, TOML};
+    use super::{*, TokenKind::*};
+
+    fn tokens(language: &Language, line: &str) -> Vec<(std::string::String, TokenKind)> {
+        let row = Row::new(line);
+        tokenize(language, &row, LineState::Normal).0.into_iter()
+            .map(|(from, to, kind)| (row[from..to].to_string(), kind))
+            .collect()
+    }
+
+    fn t(text: &str, kind: TokenKind) -> (std::string::String, TokenKind) {
+        (s![text], kind)
+    }
+
+    #[test]
+    fn rust_tokens() {
+        assert_eq!(
+            tokens(&RUST, "pub fn len(&self) -> usize { 42 } // Done"),
+            vec![t("pub", Keyword), t("fn", Keyword), t("self", Keyword), t("usize", Type), t("42", Number), t("// Done", Comment)]
+        );
+    }
+
+    #[test]
+    fn rust_string_with_escape_and_char() {
+        assert_eq!(
+            tokens(&RUST, r#"let s = "a \" b"; let c = '\n'; let r: &'a Row;"#),
+            vec![t("let", Keyword), t(r#""a \" b""#, String), t("let", Keyword), t(r"'\n'", String), t("let", Keyword), t("Row", Type)]
+        );
+    }
+
+    #[test]
+    fn json_string_with_colon_is_not_key() {
+        assert_eq!(tokens(&JSON, r#"  "http://x","#), vec![t(r#""http://x""#, String)]);
+    }
+
+    #[test]
+    fn json_keys_and_values() {
+        assert_eq!(
+            tokens(&JSON, r#"  "name": "picow", "ok": true, "n": 1.5e3"#),
+            vec![t(r#""name""#, Key), t(r#""picow""#, String), t(r#""ok""#, String), t("true", Literal), t(r#""n""#, String), t("1.5e3", Number)]
+        );
+    }
+
+    #[test]
+    fn toml_table_and_key() {
+        assert_eq!(tokens(&TOML, "[dependencies]"), vec![t("[dependencies]", Heading)]);
+        assert_eq!(tokens(&TOML, r#"unicode-width = "0.1" # pinned"#), vec![t("unicode-width", Key), t(r#""0.1""#, String), t("# pinned", Comment)]);
+    }
+
+    #[test]
+    fn powershell_is_case_insensitive() {
+        assert_eq!(
+            tokens(&POWERSHELL, "ForEach ($item in $items) { Write-Host 'x' }"),
+            vec![t("ForEach", Keyword), t("$item", Variable), t("in", Keyword), t("$items", Variable), t("'x'", String)]
+        );
+    }
+
+    #[test]
+    fn batch_comment_and_variable() {
+        assert_eq!(tokens(&BATCH, "rem setup"), vec![t("rem setup", Comment)]);
+        assert_eq!(tokens(&BATCH, "SET x=%PATH% 50%"), vec![t("SET", Keyword), t("%PATH%", Variable), t("50", Number)]);
+    }
+
+    #[test]
+    fn markdown_heading() {
+        assert_eq!(tokens(&MARKDOWN, "## Usage `picow`"), vec![t("## Usage `picow`", Heading)]);
+    }
+
+    #[test]
+    fn block_comment_continues_on_next_lines() {
+        let content = EditorContent::new(vecr!["let a; /* start", "still", "end */ let b;"], s!["\n"]);
+        let mut highlighter = Highlighter::new(Some(&RUST));
+        highlighter.update(&content, 3);
+
+        assert_eq!(highlighter.tokens(1), &[(0, 5, Comment)]);
+        assert_eq!(highlighter.tokens(2), &[(0, 6, Comment), (7, 10, Keyword)]);
+    }
+
+    #[test]
+    fn unterminated_single_line_string_ends_at_line_end() {
+        let content = EditorContent::new(vecr![r#"x = "open"#, "y = 1"], s!["\n"]);
+        let mut highlighter = Highlighter::new(Some(&TOML));
+        highlighter.update(&content, 2);
+
+        assert_eq!(highlighter.tokens(1), &[(0, 1, Key), (4, 5, Number)]);
+    }
+
+    #[test]
+    fn invalidate_rehighlights_from_row() {
+        let mut content = EditorContent::new(vecr!["let a;", "let b;"], s!["\n"]);
+        let mut highlighter = Highlighter::new(Some(&RUST));
+        highlighter.update(&content, 2);
+
+        content.lines[0] = Row::new("/* let a;");
+        highlighter.invalidate(0);
+        highlighter.update(&content, 2);
+        assert_eq!(highlighter.tokens(1), &[(0, 6, Comment)]);
+    }
+
+    #[test]
+    fn language_by_extension() {
+        assert_eq!(language("src/main.RS").map(|language| language.name), Some("Rust"));
+        assert_eq!(language("build.cmd").map(|language| language.name), Some("Batch"));
+        assert!(language("notes.txt").is_none());
+    }
+}
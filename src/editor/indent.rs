@@ -0,0 +1,178 @@
+use std::fmt;
+
+use super::row::Row;
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Indent {
+    Spaces(usize),
+    Tabs,
+}
+
+impl Default for Indent {
+    fn default() -> Self {
+        Indent::Spaces(4)
+    }
+}
+
+impl Indent {
+    pub fn unit(&self) -> String {
+        match self {
+            Indent::Spaces(n) => " ".repeat(*n),
+            Indent::Tabs => "\t".to_string(),
+        }
+    }
+
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "t" | "tab" | "tabs" => Some(Indent::Tabs),
+            width => width.parse().ok().filter(|width| (1..=MAX_WIDTH).contains(width)).map(Indent::Spaces),
+        }
+    }
+}
+
+impl fmt::Display for Indent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Indent::Spaces(n) => write!(f, "Spaces: {}", n),
+            Indent::Tabs => write!(f, "Tabs"),
+        }
+    }
+}
+
+const MAX_WIDTH: usize = 8;
+const SAMPLE_LINES: usize = 1000;
+
+pub fn detect(lines: &[Row]) -> Option<Indent> {
+    let (mut tabs, mut spaces, mut previous) = (0, 0, 0);
+    let mut widths = [0; MAX_WIDTH + 1];
+
+    for line in lines.iter().take(SAMPLE_LINES).filter(|line| first_non_whitespace(line) < line.len()) {
+        let leading = &line[..first_non_whitespace(line)];
+
+        if leading.starts_with('\t') {
+            tabs += 1;
+        } else if !leading.is_empty() && leading.chars().all(|c| c == ' ') {
+            spaces += 1;
+        }
+
+        if leading.contains('\t') {
+            continue;
+        }
+        let width = leading.len().abs_diff(previous);
+        if (2..=MAX_WIDTH).contains(&width) {
+            widths[width] += 1;
+        }
+        previous = leading.len();
+    }
+
+    match tabs > spaces {
+        true => Some(Indent::Tabs),
+        false if spaces == 0 => None,
+        false => (2..=MAX_WIDTH).rev().max_by_key(|&width| widths[width]).filter(|&width| widths[width] > 0).map(Indent::Spaces),
+    }
+}
+
+
+pub fn first_non_whitespace(row: &Row) -> usize {
+    (0..row.len()).find(|&col| !row[col..col + 1].chars().all(char::is_whitespace)).unwrap_or(row.len())
+}
+
+pub fn new_line(row: &Row, col: usize, indent: Indent) -> String {
+    let leading = &row[..first_non_whitespace(row).min(col)];
+    let opens_block = row[..col].trim_end().ends_with(['{', '[', '(', ':']);
+
+    format!("\n{}{}", leading, if opens_block { indent.unit() } else { String::new() })
+}
+
+pub fn tab(row: &Row, col: usize, indent: Indent) -> String {
+    match indent {
+        Indent::Spaces(n) => " ".repeat(n - row.mono_col_at(col) % n.max(1)),
+        Indent::Tabs => indent.unit(),
+    }
+}
+
+pub fn outdent_from(row: &Row, col: usize, indent: Indent) -> Option<usize> {
+    if col == 0 || col > first_non_whitespace(row) {
+        return None;
+    }
+
+    match indent {
+        Indent::Spaces(n) if row[..col].chars().all(|c| c == ' ') => Some(col - ((col - 1) % n.max(1) + 1)),
+        _ => None
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::vecr;
+    use super::*;
+
+    #[test]
+    fn new_line_keeps_indent() {
+        assert_eq!(new_line(&Row::new("    let a = 1;"), 14, Indent::default()), "\n    ");
+    }
+
+    #[test]
+    fn new_line_indents_after_bracket() {
+        assert_eq!(new_line(&Row::new("\tfn main() {"), 12, Indent::Tabs), "\n\t\t");
+        assert_eq!(new_line(&Row::new("if a:  "), 7, Indent::Spaces(2)), "\n  ");
+    }
+
+    #[test]
+    fn new_line_inside_leading_whitespace() {
+        assert_eq!(new_line(&Row::new("    foo"), 2, Indent::default()), "\n  ");
+    }
+
+    #[test]
+    fn first_non_whitespace_of_blank_row() {
+        assert_eq!(first_non_whitespace(&Row::new("  ")), 2);
+    }
+
+    #[test]
+    fn tab_to_next_stop() {
+        assert_eq!(tab(&Row::new("ab"), 2, Indent::default()), "  ");
+        assert_eq!(tab(&Row::new("ab"), 0, Indent::Spaces(2)), "  ");
+        assert_eq!(tab(&Row::new("ab"), 1, Indent::Tabs), "\t");
+    }
+
+    #[test]
+    fn detect_spaces() {
+        let lines = vecr!["fn main() {", "  if a {", "    b();", "  }", "", "  /*", "   * c", "   */", "}"];
+        assert_eq!(detect(&lines), Some(Indent::Spaces(2)));
+    }
+
+    #[test]
+    fn detect_tabs() {
+        let lines = vecr!["fn main() {", "\tif a {", "\t\tb();", "\t}", "}"];
+        assert_eq!(detect(&lines), Some(Indent::Tabs));
+    }
+
+    #[test]
+    fn detect_nothing_without_indent() {
+        let lines = vecr!["a", "b", "  "];
+        assert_eq!(detect(&lines), None);
+    }
+
+    #[test]
+    fn parse_indent() {
+        assert_eq!(Indent::parse(" Tabs"), Some(Indent::Tabs));
+        assert_eq!(Indent::parse("3"), Some(Indent::Spaces(3)));
+        assert_eq!(Indent::parse("0"), None);
+        assert_eq!(Indent::parse("x"), None);
+    }
+
+    #[test]
+    fn outdent_to_previous_level() {
+        let row = Row::new("      foo");
+        assert_eq!(outdent_from(&row, 6, Indent::default()), Some(4));
+        assert_eq!(outdent_from(&row, 4, Indent::default()), Some(0));
+        assert_eq!(outdent_from(&row, 7, Indent::default()), None);
+    }
+
+    #[test]
+    fn outdent_only_spaces() {
+        assert_eq!(outdent_from(&Row::new("\t  foo"), 3, Indent::default()), None);
+    }
+}
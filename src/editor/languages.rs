@@ -0,0 +1,158 @@
+use std::path::Path;
+
+use super::highlight::TokenKind;
+
+
+pub struct StringRule {
+    pub open: &'static str,
+    pub close: &'static str,
+    pub escape: Option<char>,
+    pub multiline: bool,
+}
+
+pub struct Language {
+    pub name: &'static str,
+    pub extensions: &'static [&'static str],
+    pub case_sensitive: bool,
+    pub line_comments: &'static [&'static str],
+    pub block_comments: &'static [(&'static str, &'static str)],
+    pub strings: &'static [StringRule],
+    pub char_literals: bool,
+    pub line_starts: &'static [(&'static str, TokenKind)],
+    pub key_separators: &'static [char],
+    pub variables: &'static [(&'static str, &'static str)],
+    pub word_chars: &'static [char],
+    pub keywords: &'static [&'static str],
+    pub types: &'static [&'static str],
+    pub capitalized_types: bool,
+    pub literals: &'static [&'static str],
+}
+
+const PLAIN: Language = Language {
+    name: "Plain",
+    extensions: &[],
+    case_sensitive: true,
+    line_comments: &[],
+    block_comments: &[],
+    strings: &[],
+    char_literals: false,
+    line_starts: &[],
+    key_separators: &[],
+    variables: &[],
+    word_chars: &[],
+    keywords: &[],
+    types: &[],
+    capitalized_types: false,
+    literals: &[],
+};
+
+const fn string(open: &'static str, close: &'static str, escape: Option<char>, multiline: bool) -> StringRule {
+    StringRule { open, close, escape, multiline }
+}
+
+pub const RUST: Language = Language {
+    name: "Rust",
+    extensions: &["rs"],
+    line_comments: &["//"],
+    block_comments: &[("/*", "*/")],
+    strings: &[string("r#\"", "\"#", None, true), string("r\"", "\"", None, true), string("\"", "\"", Some('\\'), true)],
+    char_literals: true,
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "fn", "for",
+        "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self", "static",
+        "struct", "super", "trait", "type", "unsafe", "use", "where", "while",
+    ],
+    types: &[
+        "bool", "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32", "f64",
+    ],
+    capitalized_types: true,
+    literals: &["true", "false"],
+    ..PLAIN
+};
+
+pub const TOML: Language = Language {
+    name: "TOML",
+    extensions: &["toml"],
+    line_comments: &["#"],
+    strings: &[
+        string("\"\"\"", "\"\"\"", Some('\\'), true), string("'''", "'''", None, true),
+        string("\"", "\"", Some('\\'), false), string("'", "'", None, false),
+    ],
+    line_starts: &[("[", TokenKind::Heading)],
+    key_separators: &['='],
+    word_chars: &['-'],
+    literals: &["true", "false"],
+    ..PLAIN
+};
+
+pub const JSON: Language = Language {
+    name: "JSON",
+    extensions: &["json"],
+    strings: &[string("\"", "\"", Some('\\'), false)],
+    key_separators: &[':'],
+    literals: &["true", "false", "null"],
+    ..PLAIN
+};
+
+pub const MARKDOWN: Language = Language {
+    name: "Markdown",
+    extensions: &["md", "markdown"],
+    strings: &[string("```", "```", None, true), string("`", "`", None, false)],
+    line_starts: &[("#", TokenKind::Heading), (">", TokenKind::Comment)],
+    ..PLAIN
+};
+
+pub const POWERSHELL: Language = Language {
+    name: "PowerShell",
+    extensions: &["ps1", "psm1", "psd1"],
+    case_sensitive: false,
+    line_comments: &["#"],
+    block_comments: &[("<#", "#>")],
+    strings: &[
+        string("@\"", "\"@", None, true), string("@'", "'@", None, true),
+        string("\"", "\"", Some('`'), true), string("'", "'", None, true),
+    ],
+    variables: &[("$", "")],
+    word_chars: &['-'],
+    keywords: &[
+        "begin", "break", "catch", "class", "continue", "do", "dynamicparam", "else", "elseif", "end", "exit", "filter",
+        "finally", "for", "foreach", "function", "if", "in", "param", "process", "return", "switch", "throw", "trap",
+        "try", "until", "using", "while",
+    ],
+    ..PLAIN
+};
+
+pub const BATCH: Language = Language {
+    name: "Batch",
+    extensions: &["bat", "cmd"],
+    case_sensitive: false,
+    line_comments: &["REM ", "@REM ", "::"],
+    strings: &[string("\"", "\"", None, false)],
+    line_starts: &[(":", TokenKind::Key)],
+    variables: &[("%", "%"), ("!", "!")],
+    keywords: &[
+        "call", "cd", "cls", "defined", "do", "echo", "else", "endlocal", "equ", "errorlevel", "exist", "exit", "for",
+        "geq", "goto", "gtr", "if", "in", "leq", "lss", "neq", "not", "pause", "popd", "pushd", "set", "setlocal",
+        "shift", "start",
+    ],
+    ..PLAIN
+};
+
+pub const INI: Language = Language {
+    name: "INI",
+    extensions: &["ini", "cfg", "editorconfig"],
+    line_comments: &[";", "#"],
+    line_starts: &[("[", TokenKind::Heading)],
+    key_separators: &['='],
+    ..PLAIN
+};
+
+const LANGUAGES: [&Language; 7] = [&RUST, &TOML, &JSON, &MARKDOWN, &POWERSHELL, &BATCH, &INI];
+
+
+pub fn language(file_name: &str) -> Option<&'static Language> {
+    let path = Path::new(file_name);
+    let extension = path.extension().or_else(|| path.file_name())?.to_string_lossy().trim_start_matches('.').to_lowercase();
+
+    LANGUAGES.into_iter().find(|language| language.extensions.contains(&extension.as_str()))
+}
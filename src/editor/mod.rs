@@ -1,12 +1,26 @@
 pub mod macros;
 
+pub mod brackets;
+pub mod caret;
 pub mod clipboard;
+pub mod comment;
 pub mod content;
 pub mod cursor;
 pub mod edit;
 pub mod events;
+pub mod goto;
+pub mod highlight;
+pub mod indent;
+pub mod languages;
+pub mod pairs;
 pub mod pos;
+pub mod prompt;
 pub mod renderer;
 pub mod row;
+pub mod search;
+pub mod settings;
+pub mod shell;
 pub mod state;
+pub mod transform;
+pub mod undo;
 pub mod viewport;
@@ -0,0 +1,68 @@
+use super::row::Row;
+
+
+const PAIRS: [(char, char); 6] = [('(', ')'), ('[', ']'), ('{', '}'), ('"', '"'), ('\'', '\''), ('`', '`')];
+
+pub fn closing(open: char) -> Option<char> {
+    PAIRS.iter().find(|&&(o, _)| o == open).map(|&(_, close)| close)
+}
+
+pub fn should_close(row: &Row, col: usize, open: char) -> bool {
+    let Some(close) = closing(open) else { return false };
+
+    let is_next_free = match char_at(row, col) {
+        Some(next) => next.is_whitespace() || PAIRS.iter().any(|&(_, close)| close == next),
+        None => true
+    };
+    let is_prev_word = col > 0 && char_at(row, col - 1).is_some_and(|c| c.is_alphanumeric() || c == '_');
+
+    is_next_free && !(open == close && is_prev_word)
+}
+
+pub fn is_step_over(row: &Row, col: usize, c: char) -> bool {
+    PAIRS.iter().any(|&(_, close)| close == c) && char_at(row, col) == Some(c)
+}
+
+pub fn is_empty_pair(row: &Row, col: usize) -> bool {
+    col > 0 && char_at(row, col - 1).and_then(closing).is_some_and(|close| char_at(row, col) == Some(close))
+}
+
+fn char_at(row: &Row, col: usize) -> Option<char> {
+    if col < row.len() { row[col..col + 1].chars().next() } else { None }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn close_before_whitespace_or_closer() {
+        assert!(should_close(&Row::new("a  b"), 1, '('));
+        assert!(should_close(&Row::new("f()"), 2, '['));
+        assert!(should_close(&Row::new("end"), 3, '{'));
+        assert!(!should_close(&Row::new("foo"), 0, '('));
+        assert!(!should_close(&Row::new("foo"), 0, 'x'));
+    }
+
+    #[test]
+    fn quote_after_word_is_not_closed() {
+        assert!(!should_close(&Row::new("don"), 3, '\''));
+        assert!(should_close(&Row::new("a = "), 4, '"'));
+        assert!(should_close(&Row::new("a = "), 4, '`'));
+    }
+
+    #[test]
+    fn step_over_closer() {
+        assert!(is_step_over(&Row::new("f()"), 2, ')'));
+        assert!(is_step_over(&Row::new("\"\""), 1, '"'));
+        assert!(!is_step_over(&Row::new("f()"), 1, '('));
+    }
+
+    #[test]
+    fn empty_pair() {
+        assert!(is_empty_pair(&Row::new("[]"), 1));
+        assert!(!is_empty_pair(&Row::new("[x]"), 1));
+        assert!(!is_empty_pair(&Row::new("[]"), 0));
+    }
+}
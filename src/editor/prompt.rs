@@ -0,0 +1,97 @@
+use crate::{s, terminal::events::{Event::{self, *}, KeyCode::*}};
+
+use super::row::Row;
+
+
+#[derive(PartialEq, Debug, Clone, Copy)]
+pub enum PromptKind {
+    Find,
+    Replace,
+    ReplaceWith,
+    GoTo,
+    Transform,
+    Indent,
+    Filter,
+    InsertOutput,
+}
+
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub label: String,
+    pub input: Row,
+    pub cursor: usize,
+}
+
+impl Prompt {
+    pub fn new(kind: PromptKind, label: &str, input: &str) -> Self {
+        let input = Row::new(input);
+        Self { kind, label: s![label], cursor: input.len(), input }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.input[..]
+    }
+
+    pub fn process(&mut self, event: &Event) -> bool {
+        match event {
+            Key(Char(c), 0) => self.replace(self.cursor, self.cursor, &c.to_string()),
+            Paste(s) => self.replace(self.cursor, self.cursor, s.lines().next().unwrap_or("")),
+            Key(Backspace, 0) if self.cursor > 0 => self.replace(self.cursor - 1, self.cursor, ""),
+            Key(Delete, 0) if self.cursor < self.input.len() => self.replace(self.cursor, self.cursor + 1, ""),
+            Key(Left, 0) => self.move_to(self.cursor.saturating_sub(1)),
+            Key(Right, 0) => self.move_to(self.cursor + 1),
+            Key(Home, 0) => self.move_to(0),
+            Key(End, 0) => self.move_to(self.input.len()),
+            _ => return false
+        }
+        true
+    }
+
+    fn replace(&mut self, from: usize, to: usize, str: &str) {
+        let text = format!("{}{}{}", &self.input[..from], str, &self.input[to..]);
+        self.cursor = from + Row::new(str).len();
+        self.input = Row::new(&text);
+    }
+
+    fn move_to(&mut self, cursor: usize) {
+        self.cursor = cursor.min(self.input.len());
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn prompt(input: &str) -> Prompt {
+        Prompt::new(PromptKind::Find, "Find: ", input)
+    }
+
+    #[test]
+    fn type_chars() {
+        let mut prompt = prompt("");
+        prompt.process(&Key(Char('ž'), 0));
+        prompt.process(&Key(Char('!'), 0));
+        assert_eq!((prompt.text(), prompt.cursor), ("ž!", 2));
+    }
+
+    #[test]
+    fn backspace_in_middle() {
+        let mut prompt = prompt("Hello");
+        prompt.process(&Key(Left, 0));
+        prompt.process(&Key(Backspace, 0));
+        assert_eq!((prompt.text(), prompt.cursor), ("Helo", 3));
+    }
+
+    #[test]
+    fn paste_first_line() {
+        let mut prompt = prompt("");
+        prompt.process(&Paste(s!["Hello\nWorld"]));
+        assert_eq!((prompt.text(), prompt.cursor), ("Hello", 5));
+    }
+
+    #[test]
+    fn unhandled_event() {
+        assert!(!prompt("").process(&Key(Up, 0)));
+    }
+}
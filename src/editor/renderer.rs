@@ -1,7 +1,7 @@
-use std::cmp::min;
+use std::{cmp::min, time::SystemTime};
 
-use crate::{s, terminal::commands::Command::{self, *}};
-use super::{row::Row, state::{EditorState, Selection}, viewport::Viewport};
+use crate::{s, terminal::commands::{Command::{self, *}, CursorShape}};
+use super::{highlight::{Token, TokenKind}, prompt::{Prompt, PromptKind}, row::Row, settings::Colors, state::EditorState, transform, undo::UndoNode, viewport::Viewport};
 
 
 pub fn render(state: &EditorState, rerender_content: bool) -> Vec<Command> {
@@ -10,11 +10,28 @@ pub fn render(state: &EditorState, rerender_content: bool) -> Vec<Command> {
         if rerender_content {
             render_content(state, commands);
         }
+        if let Some(selected) = state.undo_browser {
+            render_undo_browser(state, selected, commands);
+        }
+        if let Some(ref message) = state.overlay {
+            render_overlay(state, message, commands);
+        }
         render_status_bar(state, commands);
         render_cursor(state, commands);
     })
 }
 
+pub fn render_question(state: &EditorState, question: &str) -> Vec<Command> {
+    buffer(|commands| {
+        hide_cursor(commands);
+        render_content(state, commands);
+        clear_row(state.viewport.height + 1, commands);
+        commands.push(Print(s![question]));
+        commands.push(SetCursorShape(CursorShape::Bar));
+        commands.push(ShowCursor);
+    })
+}
+
 fn buffer<F>(mut action: F) -> Vec<Command>
 where
     F: FnMut(&mut Vec<Command>) -> (),
@@ -25,12 +42,11 @@ where
 }
 
 fn render_content(state: &EditorState, commands: &mut Vec<Command>) {
-    let selection = state.selection();
     let visible_rows = visible_rows(state);
     let visible_rows_count = visible_rows.len();
 
     for (i, row) in visible_rows.iter().enumerate() {
-        render_row(i, row, &state.viewport, selection, commands)
+        render_row(i, row, state, commands)
     }
 
     for i in visible_rows_count..state.viewport.height as usize {
@@ -45,43 +61,142 @@ fn visible_rows(state: &EditorState) -> &[Row] {
     &state.content.lines[top..bottom]
 }
 
-fn render_row(i: usize, row: &Row, viewport: &Viewport, selection: Selection, commands: &mut Vec<Command>) {
-    let (pre_selection, selected, post_selection) = visible_row_part(i, row, viewport, selection);
 
-    commands.push(MoveTo(1, 1 + i as u16));
-    commands.push(Print(pre_selection));
+#[derive(Clone, Copy, PartialEq)]
+enum Style {
+    Default,
+    Match,
+    Selected,
+    Bracket,
+    Caret,
+}
 
-    commands.push(SetBackgroundColor(100));
-    commands.push(Print(selected));
-    commands.push(SetBackgroundColor(0));
+struct RowStyles<'a> {
+    spans: Vec<(usize, usize, Style)>,
+    tokens: &'a [Token],
+}
 
-    commands.push(Print(post_selection));
-    commands.push(ClearToEndOfLine);
+impl<'a> RowStyles<'a> {
+    fn new(tokens: &'a [Token]) -> Self {
+        Self { spans: Vec::new(), tokens }
+    }
+
+    fn paint(&mut self, from: usize, to: usize, style: Style) {
+        self.spans.push((from, to, style));
+    }
+
+    fn style_at(&self, col: usize) -> Style {
+        self.spans.iter().rev()
+            .find(|&&(from, to, _)| from <= col && col < to)
+            .map(|&(_, _, style)| style)
+            .unwrap_or(Style::Default)
+    }
+
+    fn token_at(&self, col: usize) -> Option<TokenKind> {
+        self.tokens.iter()
+            .find(|&&(from, to, _)| from <= col && col < to)
+            .map(|&(_, _, kind)| kind)
+    }
 }
 
-fn visible_row_part(i: usize, row: &Row, viewport: &Viewport, selection: Selection) -> (String, String, String) {
-    let start = viewport.left;
-    if row.len() < start { return (s![""], s![""], s![""]) }
+fn row_styles<'a>(row_idx: usize, row: &Row, state: &'a EditorState) -> RowStyles<'a> {
+    let mut styles = RowStyles::new(state.tokens(row_idx));
 
-    let width = viewport.width as usize;
-    let len = min(row.len() - start, width);
+    if state.prompt.is_some() {
+        for &((_, from), (_, to)) in state.search.matches_on_row(row_idx) {
+            styles.paint(from, to, Style::Match);
+        }
+    }
 
-    let row_idx = viewport.top + i;
-    match selection {
-        Some(((sel_start_row, sel_start_col), (sel_end_row, sel_end_col)))
-            if row_idx >= sel_start_row && row_idx <= sel_end_row => {
-                let sel_start = if row_idx == sel_start_row { sel_start_col + start } else { start };
-                let sel_end = if row_idx == sel_end_row { sel_end_col + start } else { row.len() + start };
+    let selections = state.carets().iter().map(|caret| caret.selection()).chain([state.selection()]);
+    for ((from_row, from_col), (to_row, to_col)) in selections.flatten() {
+        if row_idx >= from_row && row_idx <= to_row {
+            let from = if row_idx == from_row { from_col } else { 0 };
+            let to = if row_idx == to_row { to_col } else { row.len() + 1 };
+            styles.paint(from, to, Style::Selected);
+        }
+    }
 
-                let pre_selection = if sel_start > start { s![row[start..sel_start]] } else { s![""] };
-                let selected = s![row[sel_start..sel_end]] +
-                    if row_idx < sel_end_row && sel_end >= start + len { " " } else { "" };
-                let post_selection = if sel_end < start + len { s![row[sel_end..start + len]] } else { s![""] };
+    for (bracket_row, col) in state.brackets().into_iter().flat_map(|(bracket, partner)| [bracket, partner]) {
+        if bracket_row == row_idx {
+            styles.paint(col, col + 1, Style::Bracket);
+        }
+    }
 
-                (pre_selection, selected, post_selection)
-            },
-        _ => (s![row[start..start + len]], s![""], s![""])
+    for (caret_row, col) in state.carets().iter().map(|caret| caret.cursor.pos()) {
+        if caret_row == row_idx {
+            styles.paint(col, col + 1, Style::Caret);
+        }
     }
+
+    styles
+}
+
+fn render_row(i: usize, row: &Row, state: &EditorState, commands: &mut Vec<Command>) {
+    let Viewport { left, top, width, .. } = state.viewport;
+    let styles = row_styles(top + i, row, state);
+
+    let start = min(left, row.len());
+    let end = min(left + width as usize, row.len());
+
+    commands.push(MoveTo(1, 1 + i as u16));
+
+    let mut col = start;
+    while col < end {
+        let (style, token) = (styles.style_at(col), styles.token_at(col));
+        let span_end = (col..end).find(|&c| styles.style_at(c) != style || styles.token_at(c) != token).unwrap_or(end);
+
+        set_style(style, &state.settings.colors, commands);
+        set_token_color(style, token, &state.settings.colors, commands);
+        commands.push(Print(s![row[col..span_end]]));
+        col = span_end;
+    }
+
+    let end_style = styles.style_at(row.len());
+    if row.len() >= left && row.len() < left + width as usize && end_style != Style::Default {
+        set_style(end_style, &state.settings.colors, commands);
+        commands.push(Print(s![" "]));
+    }
+
+    set_style(Style::Default, &state.settings.colors, commands);
+    commands.push(ClearToEndOfLine);
+}
+
+fn set_style(style: Style, colors: &Colors, commands: &mut Vec<Command>) {
+    commands.push(SetBackgroundColor(0));
+
+    match style {
+        Style::Default => {},
+        Style::Match => {
+            commands.push(SetBackgroundColor(colors.search_match.background()));
+            commands.push(SetForegroundColor(30));
+        },
+        Style::Selected => commands.push(SetBackgroundColor(colors.selection.background())),
+        Style::Bracket => commands.push(SetBackgroundColor(colors.bracket.background())),
+        Style::Caret => {
+            commands.push(SetBackgroundColor(colors.caret.background()));
+            commands.push(SetForegroundColor(30));
+        },
+    }
+}
+
+fn set_token_color(style: Style, token: Option<TokenKind>, colors: &Colors, commands: &mut Vec<Command>) {
+    if matches!(style, Style::Match | Style::Caret) {
+        return;
+    }
+
+    let color = match token {
+        Some(TokenKind::Comment) => colors.comment,
+        Some(TokenKind::Keyword) => colors.keyword,
+        Some(TokenKind::Type) => colors.types,
+        Some(TokenKind::String) => colors.string,
+        Some(TokenKind::Number | TokenKind::Literal) => colors.number,
+        Some(TokenKind::Key) => colors.key,
+        Some(TokenKind::Heading) => colors.heading,
+        Some(TokenKind::Variable) => colors.variable,
+        None => return,
+    };
+    commands.push(SetForegroundColor(color.foreground()));
 }
 
 fn clear_row(row: u16, commands: &mut Vec<Command>) {
@@ -89,14 +204,158 @@ fn clear_row(row: u16, commands: &mut Vec<Command>) {
     commands.push(ClearLine);
 }
 
+const UNDO_BROWSER_WIDTH: usize = 40;
+const UNDO_BROWSER_HEIGHT: usize = 16;
+const UNDO_BROWSER_HELP: &str = "Undo history: Up/Down to select, Enter to go to state, Esc to close";
+
+fn render_undo_browser(state: &EditorState, selected: usize, commands: &mut Vec<Command>) {
+    let history = state.history();
+    let nodes = history.nodes();
+
+    let width = min(UNDO_BROWSER_WIDTH, state.viewport.width as usize);
+    let height = min(min(UNDO_BROWSER_HEIGHT, state.viewport.height as usize), nodes.len());
+    let newest = min(nodes.len() - 1, selected + height / 2).max(height - 1);
+    let left = state.viewport.width as usize - width + 1;
+
+    for (i, idx) in (newest + 1 - height..=newest).rev().enumerate() {
+        let current = if idx == history.current() { '*' } else { ' ' };
+        let line = format!(" {} #{:<5} {:>8}  {}", current, idx, age(nodes[idx].at), undo_node_label(idx, &nodes[idx]));
+
+        set_style(if idx == selected { Style::Match } else { Style::Selected }, &state.settings.colors, commands);
+        commands.push(MoveTo(left as u16, 1 + i as u16));
+        commands.push(Print(format!("{:<width$.width$}", line, width = width)));
+    }
+    set_style(Style::Default, &state.settings.colors, commands);
+}
+
+const OVERLAY_HELP: &str = "Press any key to close";
+
+fn render_overlay(state: &EditorState, message: &str, commands: &mut Vec<Command>) {
+    let Viewport { width, height, .. } = state.viewport;
+
+    for (i, line) in message.lines().take(height as usize).enumerate() {
+        set_style(Style::Match, &state.settings.colors, commands);
+        commands.push(MoveTo(1, 1 + i as u16));
+        commands.push(Print(format!(" {:<width$.width$}", line, width = width as usize - 1)));
+    }
+    set_style(Style::Default, &state.settings.colors, commands);
+}
+
+fn undo_node_label(idx: usize, node: &UndoNode) -> String {
+    match node.step.ops.first() {
+        Some(op) if idx > 0 => format!("{} edits, line {}", node.step.ops.len(), op.from().0 + 1),
+        _ => s!["original"]
+    }
+}
+
+fn age(at: SystemTime) -> String {
+    let secs = at.elapsed().map(|elapsed| elapsed.as_secs()).unwrap_or(0);
+
+    match secs {
+        0..=59 => format!("{}s ago", secs),
+        60..=3599 => format!("{}m ago", secs / 60),
+        _ => format!("{}h ago", secs / 3600),
+    }
+}
+
+
 fn render_status_bar(state: &EditorState, commands: &mut Vec<Command>) {
+    clear_row(state.viewport.height + 1, commands);
+
+    match state.prompt {
+        Some(ref prompt) => render_prompt(prompt, state, commands),
+        None if state.undo_browser.is_some() => commands.push(Print(s![UNDO_BROWSER_HELP])),
+        None if state.overlay.is_some() => commands.push(Print(s![OVERLAY_HELP])),
+        None => commands.push(Print(status(state)))
+    }
+}
+
+fn status(state: &EditorState) -> String {
     let Viewport { top, width, height, .. } = state.viewport;
     let (row, col) = state.cursor.pos();
 
-    let status = format!("{}x{} | {} {} | {} | {}", width, height, row + 1, col + 1, top + 1, delimiter_label(&state.content.delimiter));
+    let mut status = state.settings.status_format
+        .replace("{size}", &format!("{}x{}", width, height))
+        .replace("{position}", &format!("{} {}", row + 1, col + 1))
+        .replace("{top}", &(top + 1).to_string())
+        .replace("{eol}", delimiter_label(&state.content.delimiter))
+        .replace("{indent}", &state.indent.to_string())
+        .replace("{mode}", if state.is_overwrite { "OVR" } else { "INS" });
 
-    clear_row(state.viewport.height + 1, commands);
-    commands.push(Print(status));
+    if let Some((_, (partner_row, _))) = state.brackets() {
+        if partner_row < top || partner_row >= top + height as usize {
+            status.push_str(&format!(" | match line {}: {}", partner_row + 1, state.content.lines[partner_row][..].trim()));
+        }
+    }
+
+    if state.history().is_truncated() {
+        status.push_str(" | history truncated");
+    }
+
+    match state.search.index_of(state.selection()) {
+        Some(idx) => format!("{} | match {} of {}", status, idx + 1, state.search.matches.len()),
+        None => status
+    }
+}
+
+fn render_prompt(prompt: &Prompt, state: &EditorState, commands: &mut Vec<Command>) {
+    let text = format!("{}{}", prompt.label, prompt.text());
+    let info = match prompt.kind {
+        PromptKind::Find | PromptKind::Replace => search_info(state),
+        PromptKind::ReplaceWith => format!("Enter replace  F3 skip  Alt+A all  {:>16} ", matches_info(state)),
+        PromptKind::GoTo => format!("{} lines ", state.content.lines.len()),
+        PromptKind::Filter => s!["Enter to replace the selection or document with the output "],
+        PromptKind::InsertOutput => s!["Enter to insert the output at the cursor "],
+        PromptKind::Transform => format!("{} ", transform::names_starting_with(prompt.text()).join("  ")),
+        PromptKind::Indent => format!("{} ", state.indent),
+    };
+
+    commands.push(Print(text.clone()));
+
+    let (text_width, info_width) = (mono_width(&text), mono_width(&info));
+    let width = state.viewport.width as usize;
+    if text_width + info_width < width {
+        commands.push(MoveTo((width - info_width + 1) as u16, state.viewport.height + 1));
+        commands.push(Print(info));
+    }
+}
+
+fn search_info(state: &EditorState) -> String {
+    let search = &state.search;
+    let flag = |on: bool, name: &str| format!("[{}] {}", if on { 'x' } else { ' ' }, name);
+
+    format!("{}  {}  {}  {:>16} ",
+        flag(search.options.case_sensitive, "Case"),
+        flag(search.options.whole_word, "Word"),
+        flag(search.options.regex, "Regex"),
+        matches_info(state)
+    )
+}
+
+fn matches_info(state: &EditorState) -> String {
+    let search = &state.search;
+
+    let matches = if search.is_invalid {
+        s!["invalid regex"]
+    } else if search.query.is_empty() {
+        s![""]
+    } else {
+        match (search.index_of(state.selection()), search.matches.len()) {
+            (_, 0) => s!["no matches"],
+            (Some(idx), len) => format!("match {} of {}", idx + 1, len),
+            (None, len) => format!("{} matches", len),
+        }
+    };
+
+    match search.scope {
+        Some(_) if !matches.is_empty() => format!("{} in selection", matches),
+        _ => matches
+    }
+}
+
+fn mono_width(text: &str) -> usize {
+    let row = Row::new(text);
+    row.mono_col_at(row.len())
 }
 
 fn hide_cursor(commands: &mut Vec<Command>) {
@@ -104,12 +363,25 @@ fn hide_cursor(commands: &mut Vec<Command>) {
 }
 
 fn render_cursor(state: &EditorState, commands: &mut Vec<Command>) {
+    if state.undo_browser.is_some() || state.overlay.is_some() {
+        return;
+    }
+
+    if let Some(ref prompt) = state.prompt {
+        let col = mono_width(&prompt.label) + prompt.input.mono_col_at(prompt.cursor);
+        commands.push(MoveTo(col as u16 + 1, state.viewport.height + 1));
+        commands.push(SetCursorShape(CursorShape::Bar));
+        commands.push(ShowCursor);
+        return;
+    }
+
     let (row, col) = state.cursor.pos();
 
     if state.viewport.cursor_within((row, col)) {
         let col_2 = state.content.lines[row].mono_col_at(col);
         let (row_rel, col_rel) = state.viewport.to_relative((row, col_2));
         commands.push(MoveTo(col_rel, row_rel));
+        commands.push(SetCursorShape(if state.is_overwrite { CursorShape::Block } else { CursorShape::Bar }));
         commands.push(ShowCursor);
     }
 }
@@ -49,6 +49,24 @@ impl Row {
         return self.bytes.len();
     }
 
+    pub fn char_idx_of_byte(&self, byte: usize) -> usize {
+        self.byte_idx.partition_point(|&idx| idx < byte)
+    }
+
+    pub fn word_starts(&self) -> Vec<usize> {
+        self.bytes.split_word_bound_indices()
+            .filter(|(_, word)| !word.chars().all(char::is_whitespace))
+            .map(|(byte, _)| self.char_idx_of_byte(byte))
+            .collect()
+    }
+
+    pub fn word_at(&self, col: usize) -> Option<(usize, usize)> {
+        self.bytes.split_word_bound_indices()
+            .filter(|(_, word)| !word.chars().all(char::is_whitespace))
+            .map(|(byte, word)| (self.char_idx_of_byte(byte), self.char_idx_of_byte(byte + word.len())))
+            .find(|&(from, to)| from <= col && col <= to)
+    }
+
     pub fn mono_col_at(&self, char_idx: usize) -> usize {
         if char_idx < self.char_width.len() {
             return self.char_width[char_idx]
@@ -170,6 +188,15 @@ mod test {
     test! { chat_idx_at_3: row("I💖kůň").char_idx_at(3) => 2 }
     test! { chat_idx_at_5: row("I💖kůň").char_idx_at(5) => 4 }
 
+    test! { char_idx_of_byte_0: row("I💖kůň").char_idx_of_byte(0) => 0 }
+    test! { char_idx_of_byte_5: row("I💖kůň").char_idx_of_byte(5) => 2 }
+    test! { char_idx_of_byte_end: row("I💖kůň").char_idx_of_byte(10) => 5 }
+
+    test! { word_starts: row("  Hello, kůň_world  42").word_starts() => vec![2, 7, 9, 20] }
+    test! { word_at_inside: row("  Hello, kůň_world").word_at(11) => Some((9, 18)) }
+    test! { word_at_end: row("  Hello, kůň_world").word_at(7) => Some((2, 7)) }
+    test! { word_at_space: row("  Hello").word_at(1) => None }
+
     #[test]
     fn join() {
         let rows = vec![row("žlu"), row("ťoučký")];
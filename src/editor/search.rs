@@ -0,0 +1,282 @@
+use regex::{Regex, RegexBuilder};
+
+use crate::s;
+use super::{content::EditorContent, edit::EditOp, pos::{PosInDocument, PosInDocumentExt}, state::Selection};
+
+
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct SearchOptions {
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub regex: bool,
+}
+
+pub type Match = (PosInDocument, PosInDocument);
+
+#[derive(Default)]
+pub struct Search {
+    pub query: String,
+    pub options: SearchOptions,
+    pub matches: Vec<Match>,
+    pub is_invalid: bool,
+    pub scope: Selection,
+    pub replace_with: String,
+    regex: Option<Regex>,
+}
+
+impl Search {
+    pub fn update(&mut self, content: &EditorContent) {
+        match regex(&self.query, &self.options) {
+            Ok(regex) => {
+                self.matches = find_all_with(content, &self.query, &regex).into_iter()
+                    .filter(|&m| self.in_scope(m))
+                    .collect();
+                self.regex = Some(regex);
+                self.is_invalid = false;
+            },
+            Err(_) => {
+                self.matches = Vec::new();
+                self.regex = None;
+                self.is_invalid = true;
+            }
+        }
+    }
+
+    fn in_scope(&self, (from, to): Match) -> bool {
+        match self.scope {
+            Some((scope_from, scope_to)) => !from.is_before(&scope_from) && !scope_to.is_before(&to),
+            None => true
+        }
+    }
+
+    pub fn matches_on_row(&self, row: usize) -> &[Match] {
+        let from = self.matches.partition_point(|&((match_row, _), _)| match_row < row);
+        let to = self.matches.partition_point(|&((match_row, _), _)| match_row <= row);
+        &self.matches[from..to]
+    }
+
+    pub fn index_of(&self, selection: Selection) -> Option<usize> {
+        selection.and_then(|selection| self.matches.binary_search(&selection).ok())
+    }
+
+    pub fn next(&self, pos: PosInDocument, skip: Selection) -> Option<Match> {
+        let idx = self.first_from(pos);
+        let idx = if skip.is_some() && self.matches.get(idx).copied() == skip { idx + 1 } else { idx };
+        self.wrapped(idx)
+    }
+
+    pub fn prev(&self, pos: PosInDocument) -> Option<Match> {
+        let idx = self.first_from(pos);
+        self.wrapped(if idx == 0 { self.matches.len() } else { idx } - 1)
+    }
+
+    fn first_from(&self, pos: PosInDocument) -> usize {
+        self.matches.partition_point(|(from, _)| from.is_before(&pos))
+    }
+
+    fn wrapped(&self, idx: usize) -> Option<Match> {
+        if self.matches.is_empty() { None } else { Some(self.matches[idx % self.matches.len()]) }
+    }
+
+    pub fn replacement(&self, content: &EditorContent, ((row_idx, from), _): Match) -> String {
+        let Some(regex) = self.regex.as_ref().filter(|_| self.options.regex) else { return self.replace_with.clone() };
+
+        let row = &content.lines[row_idx];
+        let mut replacement = String::new();
+        if let Some(captures) = regex.captures_at(&row[..], row[..from].len()) {
+            captures.expand(&self.replace_with, &mut replacement);
+        }
+        replacement
+    }
+
+    pub fn replace_all(&self, content: &EditorContent) -> Option<EditOp> {
+        let (((first_row, _), _), ((last_row, _), _)) = (self.matches.first()?, self.matches.last()?);
+
+        let text = (*first_row..=*last_row).map(|row_idx| {
+            let row = &content.lines[row_idx];
+            let mut line = String::new();
+            let mut col = 0;
+            for &m in self.matches_on_row(row_idx) {
+                let ((_, from), (_, to)) = m;
+                line.push_str(&row[col..from]);
+                line.push_str(&self.replacement(content, m));
+                col = to;
+            }
+            line.push_str(&row[col..]);
+            line
+        }).collect::<Vec<_>>().join("\n");
+
+        Some(EditOp::replace(content, (*first_row, 0), content.line_end(*last_row), &text))
+    }
+}
+
+
+pub fn find_all(content: &EditorContent, query: &str, options: &SearchOptions) -> Result<Vec<Match>, regex::Error> {
+    if query.is_empty() { return Ok(Vec::new()) }
+
+    Ok(find_all_with(content, query, &regex(query, options)?))
+}
+
+fn find_all_with(content: &EditorContent, query: &str, regex: &Regex) -> Vec<Match> {
+    if query.is_empty() { return Vec::new() }
+
+    content.lines.iter().enumerate().flat_map(|(row_idx, row)|
+        regex.find_iter(&row[..])
+            .filter(|m| !m.is_empty())
+            .map(move |m| ((row_idx, row.char_idx_of_byte(m.start())), (row_idx, row.char_idx_of_byte(m.end()))))
+    ).collect()
+}
+
+fn regex(query: &str, options: &SearchOptions) -> Result<Regex, regex::Error> {
+    let pattern = if options.regex { s![query] } else { regex::escape(query) };
+    let pattern = if options.whole_word { format!(r"\b(?:{})\b", pattern) } else { pattern };
+
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::{s, vecr};
+    use crate::editor::row::Row;
+    use super::*;
+
+    fn content() -> EditorContent {
+        EditorContent::new(vecr![
+            "Hello World",
+            "Wonderful world, worldwide",
+            "Žluťoučký kůň world"
+        ], s!["\n"])
+    }
+
+    fn options(case_sensitive: bool, whole_word: bool, regex: bool) -> SearchOptions {
+        SearchOptions { case_sensitive, whole_word, regex }
+    }
+
+    fn search(query: &str) -> Search {
+        let mut search = Search { query: s![query], ..Default::default() };
+        search.update(&content());
+        search
+    }
+
+    #[test]
+    fn find_ignore_case() {
+        assert_eq!(
+            find_all(&content(), "WORLD", &options(false, false, false)).unwrap(),
+            vec![((0, 6), (0, 11)), ((1, 10), (1, 15)), ((1, 17), (1, 22)), ((2, 14), (2, 19))]
+        );
+    }
+
+    #[test]
+    fn find_case_sensitive() {
+        assert_eq!(
+            find_all(&content(), "World", &options(true, false, false)).unwrap(),
+            vec![((0, 6), (0, 11))]
+        );
+    }
+
+    #[test]
+    fn find_whole_word() {
+        assert_eq!(
+            find_all(&content(), "world", &options(true, true, false)).unwrap(),
+            vec![((1, 10), (1, 15)), ((2, 14), (2, 19))]
+        );
+    }
+
+    #[test]
+    fn find_unicode() {
+        assert_eq!(
+            find_all(&content(), "kůň", &options(false, false, false)).unwrap(),
+            vec![((2, 10), (2, 13))]
+        );
+    }
+
+    #[test]
+    fn find_regex() {
+        assert_eq!(
+            find_all(&content(), r"W\w+l\b", &options(true, false, true)).unwrap(),
+            vec![((1, 0), (1, 9))]
+        );
+    }
+
+    #[test]
+    fn find_literal_not_regex() {
+        assert_eq!(find_all(&content(), "W.", &options(false, false, false)).unwrap(), vec![]);
+    }
+
+    #[test]
+    fn invalid_regex() {
+        let mut search = Search { query: s!["(world"], options: options(false, false, true), ..Default::default() };
+        search.update(&content());
+        assert!(search.is_invalid);
+    }
+
+    #[test]
+    fn next_skips_current() {
+        let search = search("world");
+        assert_eq!(search.next((0, 6), Some(((0, 6), (0, 11)))), Some(((1, 10), (1, 15))));
+    }
+
+    #[test]
+    fn next_wraps_around() {
+        assert_eq!(search("world").next((2, 15), None), Some(((0, 6), (0, 11))));
+    }
+
+    #[test]
+    fn prev_wraps_around() {
+        assert_eq!(search("world").prev((0, 6)), Some(((2, 14), (2, 19))));
+    }
+
+    #[test]
+    fn update_limited_to_scope() {
+        let mut search = Search { query: s!["world"], scope: Some(((0, 8), (1, 22))), ..Default::default() };
+        search.update(&content());
+        assert_eq!(search.matches, vec![((1, 10), (1, 15)), ((1, 17), (1, 22))]);
+    }
+
+    #[test]
+    fn replacement_expands_captures() {
+        let mut search = Search { query: s![r"(\w+) (\w+)"], options: options(false, false, true), replace_with: s!["$2 $1"], ..Default::default() };
+        search.update(&content());
+        assert_eq!(search.replacement(&content(), ((2, 0), (2, 13))), "kůň Žluťoučký");
+    }
+
+    #[test]
+    fn replacement_literal() {
+        let search = Search { replace_with: s!["$1"], ..search("world") };
+        assert_eq!(search.replacement(&content(), ((0, 6), (0, 11))), "$1");
+    }
+
+    #[test]
+    fn replace_all_single_op() {
+        let search = Search { replace_with: s!["Earth"], ..search("world") };
+        assert_eq!(
+            search.replace_all(&content()),
+            Some(EditOp::Replace {
+                from: (0, 0),
+                inserted_lines: vecr!["Hello Earth", "Wonderful Earth, Earthwide", "Žluťoučký kůň Earth"],
+                deleted_lines: content().lines
+            })
+        );
+    }
+
+    #[test]
+    fn replace_all_multi_line() {
+        let search = Search { replace_with: s!["\n"], ..search("wonderful ") };
+        assert_eq!(
+            search.replace_all(&content()),
+            Some(EditOp::Replace {
+                from: (1, 0),
+                inserted_lines: vecr!["", "world, worldwide"],
+                deleted_lines: vecr!["Wonderful world, worldwide"]
+            })
+        );
+    }
+
+    #[test]
+    fn index_of_selection() {
+        assert_eq!(search("world").index_of(Some(((1, 17), (1, 22)))), Some(2));
+    }
+}
@@ -0,0 +1,121 @@
+use crate::file::save::Backup;
+use super::{indent::Indent, undo::DEFAULT_BUDGET};
+
+
+pub const STATUS_FIELDS: [&str; 6] = ["size", "position", "top", "eol", "indent", "mode"];
+const DEFAULT_STATUS: &str = "{size} | {position} | {top} | {eol} | {indent} | {mode}";
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Settings {
+    pub tab_width: usize,
+    pub use_tabs: bool,
+    pub auto_indent: bool,
+    pub auto_close_pairs: bool,
+    pub undo_budget: usize,
+    pub backup: Backup,
+    pub persist_undo: bool,
+    pub status_format: String,
+    pub colors: Colors,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            tab_width: 4,
+            use_tabs: false,
+            auto_indent: true,
+            auto_close_pairs: true,
+            undo_budget: DEFAULT_BUDGET,
+            backup: Backup::None,
+            persist_undo: true,
+            status_format: DEFAULT_STATUS.to_string(),
+            colors: Colors::default(),
+        }
+    }
+}
+
+impl Settings {
+    pub fn indent(&self) -> Indent {
+        if self.use_tabs { Indent::Tabs } else { Indent::Spaces(self.tab_width) }
+    }
+}
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Color(u8);
+
+const COLOR_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+
+impl Color {
+    pub fn parse(name: &str) -> Option<Self> {
+        let (bright, name) = match name.strip_prefix("bright-") {
+            Some(name) => (8, name),
+            None => (0, name),
+        };
+        COLOR_NAMES.iter().position(|&color| color == name).map(|idx| Color(idx as u8 + bright))
+    }
+
+    pub fn foreground(&self) -> u8 {
+        if self.0 < 8 { 30 + self.0 } else { 82 + self.0 }
+    }
+
+    pub fn background(&self) -> u8 {
+        self.foreground() + 10
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct Colors {
+    pub comment: Color,
+    pub keyword: Color,
+    pub types: Color,
+    pub string: Color,
+    pub number: Color,
+    pub key: Color,
+    pub heading: Color,
+    pub variable: Color,
+    pub selection: Color,
+    pub search_match: Color,
+    pub bracket: Color,
+    pub caret: Color,
+}
+
+impl Default for Colors {
+    fn default() -> Self {
+        let color = |name| Color::parse(name).expect("valid color name");
+        Self {
+            comment: color("green"),
+            keyword: color("bright-magenta"),
+            types: color("bright-cyan"),
+            string: color("yellow"),
+            number: color("bright-red"),
+            key: color("cyan"),
+            heading: color("bright-blue"),
+            variable: color("bright-yellow"),
+            selection: color("bright-black"),
+            search_match: color("yellow"),
+            bracket: color("blue"),
+            caret: color("white"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn color_codes() {
+        let (red, bright_red) = (Color::parse("red").unwrap(), Color::parse("bright-red").unwrap());
+        assert_eq!((red.foreground(), red.background()), (31, 41));
+        assert_eq!((bright_red.foreground(), bright_red.background()), (91, 101));
+        assert_eq!(Color::parse("purple"), None);
+    }
+
+    #[test]
+    fn indent_from_settings() {
+        assert_eq!(Settings::default().indent(), Indent::default());
+        assert_eq!(Settings { use_tabs: true, ..Settings::default() }.indent(), Indent::Tabs);
+    }
+}
@@ -0,0 +1,110 @@
+use std::{io::{Read, Write}, process::{Child, Command, ExitStatus, Stdio}, thread::{self, JoinHandle}, time::{Duration, Instant}};
+
+const TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+
+pub fn run(command: &str, input: &str) -> Result<String, String> {
+    run_with_timeout(command, input, TIMEOUT)
+}
+
+fn run_with_timeout(command: &str, input: &str, timeout: Duration) -> Result<String, String> {
+    let mut child = shell(command)
+        .stdin(Stdio::piped()).stdout(Stdio::piped()).stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("{}: {}", command, e))?;
+
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let input = input.to_string();
+    thread::spawn(move || stdin.write_all(input.as_bytes()));
+    let stdout = read_all(child.stdout.take().expect("stdout is piped"));
+    let stderr = read_all(child.stderr.take().expect("stderr is piped"));
+
+    let status = wait(&mut child, timeout).map_err(|e| format!("{}: {}", command, e))?;
+    let stdout = stdout.join().unwrap_or_default();
+    let stderr = stderr.join().unwrap_or_default();
+
+    let stderr = String::from_utf8_lossy(&stderr);
+    if !status.success() || !stderr.trim().is_empty() {
+        return Err(match stderr.trim() {
+            "" => format!("{}: {}", command, status),
+            stderr => stderr.to_string(),
+        });
+    }
+
+    let stdout = String::from_utf8_lossy(&stdout).replace("\r\n", "\n");
+    Ok(stdout.strip_suffix('\n').unwrap_or(&stdout).to_string())
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    use std::os::windows::process::CommandExt;
+
+    let mut shell = Command::new("cmd");
+    shell.arg("/C").raw_arg(command);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+fn read_all(mut pipe: impl Read + Send + 'static) -> JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        let _ = pipe.read_to_end(&mut bytes);
+        bytes
+    })
+}
+
+fn wait(child: &mut Child, timeout: Duration) -> Result<ExitStatus, String> {
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        if let Some(status) = child.try_wait().map_err(|e| e.to_string())? {
+            return Ok(status);
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(format!("killed after {:?}", timeout));
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn output_without_trailing_newline() {
+        assert_eq!(run("sort", "b\na\n"), Ok(String::from("a\nb")));
+    }
+
+    #[test]
+    fn failing_command_is_error() {
+        assert!(run("exit 3", "").unwrap_err().contains('3'));
+    }
+
+    #[test]
+    fn stderr_is_error() {
+        assert_eq!(run("echo oops 1>&2", ""), Err(String::from("oops")));
+    }
+
+    #[test]
+    fn quoted_argument() {
+        assert_eq!(run("echo \"a  b\"", ""), Ok(String::from(if cfg!(windows) { "\"a  b\"" } else { "a  b" })));
+    }
+
+    #[test]
+    fn hanging_command_is_killed() {
+        let started = Instant::now();
+        assert!(run_with_timeout("sleep 5", "", Duration::from_secs(1)).unwrap_err().contains("killed"));
+        assert!(started.elapsed() < Duration::from_secs(4));
+    }
+}
@@ -1,6 +1,10 @@
-use std::collections::LinkedList;
+use std::{cmp::Reverse, iter, mem};
 
-use super::{content::EditorContent, cursor::Cursor, edit::{self, EditOp}, pos::{PosInDocument, PosInDocumentExt}, viewport::Viewport};
+use super::{
+    brackets, caret::{self, Caret, MonoPos}, content::EditorContent, cursor::Cursor, edit::{self, EditOp}, goto,
+    highlight::{Highlighter, Token, TokenKind}, indent::Indent, languages::Language, pos::{PosInDocument, PosInDocumentExt},
+    prompt::{Prompt, PromptKind}, row::{Row, RowVecExt}, search::{self, Search, SearchOptions}, settings::Settings, shell, transform, undo::{CursorState, UndoHistory}, viewport::Viewport
+};
 
 
 pub struct EditorState {
@@ -8,21 +12,56 @@ pub struct EditorState {
     pub viewport: Viewport,
     pub cursor: Cursor,
     selection_pos: Option<PosInDocument>,
-    undo_stack: LinkedList<EditOp>,
-    redo_stack: LinkedList<EditOp>,
+    carets: Vec<Caret>,
+    column: Option<(MonoPos, MonoPos)>,
+    history: UndoHistory,
+    changes: usize,
+    pub prompt: Option<Prompt>,
+    pub search: Search,
+    search_origin: Option<(PosInDocument, Option<PosInDocument>)>,
+    jumps_back: Vec<PosInDocument>,
+    jumps_forward: Vec<PosInDocument>,
+    pub undo_browser: Option<usize>,
+    pub overlay: Option<String>,
+    pub is_overwrite: bool,
+    pub indent: Indent,
+    pub settings: Settings,
+    highlighter: Highlighter,
+    brackets: Option<(PosInDocument, PosInDocument)>,
+    brackets_at: Option<(PosInDocument, usize)>,
+    indent_override: Option<Indent>,
 }
 
+const MAX_JUMPS: usize = 100;
+
 pub type ReRenderContent = bool;
 pub type Selection = Option<(PosInDocument, PosInDocument)>;
 
 impl EditorState {
     pub fn new(content: EditorContent, viewport: Viewport, cursor_pos: PosInDocument, selection_pos: Option<PosInDocument>) -> Self {
+        let indent = content.indent.unwrap_or_default();
         Self {
             content, viewport,
             cursor: Cursor::from(cursor_pos),
             selection_pos,
-            undo_stack: LinkedList::new(),
-            redo_stack: LinkedList::new(),
+            carets: Vec::new(),
+            column: None,
+            history: UndoHistory::default(),
+            changes: 0,
+            prompt: None,
+            search: Search::default(),
+            search_origin: None,
+            jumps_back: Vec::new(),
+            jumps_forward: Vec::new(),
+            undo_browser: None,
+            overlay: None,
+            is_overwrite: false,
+            indent,
+            settings: Settings::default(),
+            highlighter: Highlighter::new(None),
+            brackets: None,
+            brackets_at: None,
+            indent_override: None,
         }
     }
 
@@ -37,6 +76,12 @@ impl EditorState {
 
 
     pub fn move_cursor(&mut self, new_cursor: Cursor, is_selection: bool) -> ReRenderContent {
+        self.history.break_coalescing();
+        self.column = None;
+        self.place_cursor(new_cursor, is_selection)
+    }
+
+    fn place_cursor(&mut self, new_cursor: Cursor, is_selection: bool) -> ReRenderContent {
         let selection_updated = self.update_selection(is_selection);
         self.cursor = new_cursor;
 
@@ -57,13 +102,141 @@ impl EditorState {
     }
 
 
+    pub fn select(&mut self, from: PosInDocument, to: PosInDocument) -> ReRenderContent {
+        self.selection_pos = Some(from);
+        self.cursor = Cursor::from(to);
+
+        if let Some(scroll_to) = self.viewport.scroll_into_view(to) {
+            self.scroll(scroll_to);
+        }
+        true
+    }
+
     pub fn select_all(&mut self) -> ReRenderContent {
+        self.clear_carets();
         self.selection_pos = Some((0, 0));
         self.cursor = Cursor::from(self.content.last_line_end());
         true
     }
 
 
+    pub fn carets(&self) -> &[Caret] {
+        &self.carets
+    }
+
+    pub fn clear_carets(&mut self) -> ReRenderContent {
+        self.column = None;
+        !mem::take(&mut self.carets).is_empty()
+    }
+
+    pub fn add_caret(&mut self, below: bool) -> ReRenderContent {
+        let rows = self.carets.iter().map(|caret| caret.cursor.pos().0).chain(iter::once(self.cursor.pos().0));
+        let row = match below {
+            true => rows.max().unwrap_or(0) + 1,
+            false => match rows.min().unwrap_or(0) { 0 => return false, row => row - 1 },
+        };
+        if row > self.content.last_line_row() {
+            return false;
+        }
+
+        let (cursor_row, cursor_col) = self.cursor.pos();
+        let mono_col = self.content.lines[cursor_row].mono_col_at(cursor_col);
+        let pos = (row, self.content.lines[row].char_idx_at(mono_col).min(self.content.line_len(row)));
+
+        self.column = None;
+        self.carets.push(Caret::new(pos, None));
+        self.scroll_to_caret(pos)
+    }
+
+    pub fn add_next_occurrence(&mut self) -> ReRenderContent {
+        let Some((from, to)) = self.selection() else { return self.select_word() };
+
+        let query = self.content.selected_text(from, to);
+        let options = SearchOptions { case_sensitive: true, ..SearchOptions::default() };
+        let Ok(matches) = search::find_all(&self.content, &query, &options) else { return false };
+
+        let is_taken = |m: &(PosInDocument, PosInDocument)|
+            Some(*m) == self.selection() || self.carets.iter().any(|caret| caret.selection() == Some(*m));
+        let next = matches.iter().filter(|m| !is_taken(m)).find(|(from, _)| !from.is_before(&to))
+            .or_else(|| matches.iter().find(|m| !is_taken(m)));
+        let Some(&(next_from, next_to)) = next else { return false };
+
+        let primary = Caret {
+            cursor: mem::replace(&mut self.cursor, Cursor::from(next_to)),
+            selection_pos: self.selection_pos.replace(next_from),
+        };
+        self.column = None;
+        self.carets.push(primary);
+        self.scroll_to_caret(next_to)
+    }
+
+    fn select_word(&mut self) -> ReRenderContent {
+        let (row, col) = self.cursor.pos();
+        match self.content.lines[row].word_at(col) {
+            Some((from, to)) => self.select((row, from), (row, to)),
+            None => false
+        }
+    }
+
+    pub fn column_corner(&self) -> MonoPos {
+        self.column.map(|(_, corner)| corner).unwrap_or_else(|| self.mono_pos(self.cursor.pos()))
+    }
+
+    pub fn select_column(&mut self, corner: MonoPos) -> ReRenderContent {
+        let anchor = self.column.map(|(anchor, _)| anchor).unwrap_or_else(|| self.mono_pos(self.cursor.pos()));
+        let corner = (corner.0.min(self.content.last_line_row()), corner.1);
+
+        let mut carets = caret::column(&self.content, anchor, corner);
+        let primary = if corner.0 < anchor.0 { carets.remove(0) } else { carets.remove(carets.len() - 1) };
+
+        self.history.break_coalescing();
+        self.carets = carets;
+        self.column = Some((anchor, corner));
+        self.selection_pos = primary.selection_pos;
+        self.cursor = primary.cursor;
+        self.scroll_to_caret(self.cursor.pos())
+    }
+
+    pub fn move_carets<F>(&mut self, is_selection: bool, move_caret: F) -> ReRenderContent
+    where
+        F: Fn(&Cursor, &EditorContent, &Viewport) -> Option<Cursor>,
+    {
+        if self.carets.is_empty() {
+            return false;
+        }
+
+        for caret in self.carets.iter_mut() {
+            let Some(cursor) = move_caret(&caret.cursor, &self.content, &self.viewport) else { continue };
+
+            caret.selection_pos = if is_selection { caret.selection_pos.or(Some(caret.cursor.pos())) } else { None };
+            caret.cursor = cursor;
+        }
+        self.column = None;
+        self.dedup_carets();
+        true
+    }
+
+    fn dedup_carets(&mut self) {
+        let mut seen = vec![self.cursor.pos()];
+        self.carets.retain(|caret| {
+            let is_new = !seen.contains(&caret.cursor.pos());
+            seen.push(caret.cursor.pos());
+            is_new
+        });
+    }
+
+    fn mono_pos(&self, (row, col): PosInDocument) -> MonoPos {
+        (row, self.content.lines[row].mono_col_at(col))
+    }
+
+    fn scroll_to_caret(&mut self, pos: PosInDocument) -> ReRenderContent {
+        if let Some(scroll_to) = self.viewport.scroll_into_view(pos) {
+            self.scroll(scroll_to);
+        }
+        true
+    }
+
+
     pub fn scroll(&mut self, scroll_to: PosInDocument) -> ReRenderContent {
         let (top, left) = scroll_to;
         self.viewport.scroll(top, left);
@@ -71,33 +244,485 @@ impl EditorState {
     }
 
 
+    pub fn open_go_to(&mut self) -> ReRenderContent {
+        self.prompt = Some(Prompt::new(PromptKind::GoTo, "Go to line[:column]: ", ""));
+        true
+    }
+
+    pub fn go_to(&mut self) -> ReRenderContent {
+        match goto::parse_target(&self.prompt_text(), self.cursor.pos(), self.content.lines.len()) {
+            Some(pos) => {
+                self.prompt = None;
+                self.jump_to(pos)
+            },
+            None => false
+        }
+    }
+
+    pub fn open_transform(&mut self) -> ReRenderContent {
+        self.clear_carets();
+        self.prompt = Some(Prompt::new(PromptKind::Transform, "Transform: ", ""));
+        true
+    }
+
+    pub fn transform(&mut self) -> ReRenderContent {
+        let Some(transform) = transform::parse(&self.prompt_text()) else { return false };
+
+        let (from, to) = self.selection().unwrap_or(((0, 0), self.content.last_line_end()));
+        let Some(text) = transform::apply(transform, &self.content.selection(from, to).join("\n")) else { return false };
+        self.prompt = None;
+
+        self.replace_keeping_selection(from, to, &text)
+    }
+
+    fn replace_keeping_selection(&mut self, from: PosInDocument, to: PosInDocument, text: &str) -> ReRenderContent {
+        let edit_op = EditOp::replace(&self.content, from, to, text);
+        let end = edit_op.to();
+        match self.selection() {
+            Some(_) if self.cursor.pos() == from => self.edit_and_place(edit_op, from, Some(end)),
+            Some(_) => self.edit_and_place(edit_op, end, Some(from)),
+            None => {
+                let lines: Vec<&str> = text.split('\n').collect();
+                let row = self.cursor.pos().0.min(lines.len() - 1);
+                let col = self.cursor.pos().1.min(Row::new(lines[row]).len());
+                self.edit_and_place(edit_op, (row, col), None)
+            }
+        }
+    }
+
+    pub fn open_indent(&mut self) -> ReRenderContent {
+        self.prompt = Some(Prompt::new(PromptKind::Indent, "Indent (tabs or width): ", ""));
+        true
+    }
+
+    pub fn set_indent(&mut self) -> ReRenderContent {
+        match Indent::parse(&self.prompt_text()) {
+            Some(indent) => {
+                self.indent = indent;
+                self.indent_override = Some(indent);
+                self.prompt = None;
+                true
+            },
+            None => false
+        }
+    }
+
+    pub fn open_shell_command(&mut self, insert: bool) -> ReRenderContent {
+        self.clear_carets();
+        self.prompt = Some(match insert {
+            true => Prompt::new(PromptKind::InsertOutput, "Insert output of: ", ""),
+            false => Prompt::new(PromptKind::Filter, "Filter through: ", ""),
+        });
+        true
+    }
+
+    pub fn run_shell_command(&mut self) -> ReRenderContent {
+        let Some(kind) = self.prompt.as_ref().map(|prompt| prompt.kind) else { return false };
+        let command = self.prompt_text();
+        if command.trim().is_empty() {
+            return false;
+        }
+        self.prompt = None;
+
+        let (from, to) = match kind {
+            PromptKind::InsertOutput => (self.cursor.pos(), self.cursor.pos()),
+            _ => self.selection().unwrap_or(((0, 0), self.content.last_line_end())),
+        };
+        let input = if from == to { String::new() } else { format!("{}\n", self.content.selection(from, to).join("\n")) };
+
+        match shell::run(&command, &input) {
+            Ok(output) if kind == PromptKind::InsertOutput => self.edit(EditOp::insert(from, &output)),
+            Ok(output) => self.replace_keeping_selection(from, to, &output),
+            Err(message) => {
+                self.overlay = Some(message);
+                true
+            }
+        }
+    }
+
+    pub fn close_overlay(&mut self) -> ReRenderContent {
+        self.overlay = None;
+        true
+    }
+
+    pub fn jump_to(&mut self, pos: PosInDocument) -> ReRenderContent {
+        if self.cursor.move_to(&self.content, pos).is_none() { return false }
+
+        if self.jumps_back.len() == MAX_JUMPS {
+            self.jumps_back.remove(0);
+        }
+        self.jumps_back.push(self.cursor.pos());
+        self.jumps_forward.clear();
+        self.move_centered(pos)
+    }
+
+    pub fn jump_back(&mut self) -> ReRenderContent {
+        let Some(pos) = self.jumps_back.pop() else { return false };
+
+        self.jumps_forward.push(self.cursor.pos());
+        self.move_centered(pos)
+    }
+
+    pub fn jump_forward(&mut self) -> ReRenderContent {
+        let Some(pos) = self.jumps_forward.pop() else { return false };
+
+        self.jumps_back.push(self.cursor.pos());
+        self.move_centered(pos)
+    }
+
+    fn move_centered(&mut self, pos: PosInDocument) -> ReRenderContent {
+        let cursor = Cursor::from((0, 0));
+        self.clear_carets();
+        self.update_selection(false);
+        self.cursor = cursor.move_to(&self.content, pos).unwrap_or(cursor);
+
+        if let Some(scroll_to) = self.viewport.center_on(self.cursor.pos()) {
+            self.scroll(scroll_to);
+        }
+        true
+    }
+
+
+    pub fn changes(&self) -> usize {
+        self.changes
+    }
+
+
+    pub fn toggle_overwrite(&mut self) -> ReRenderContent {
+        self.is_overwrite = !self.is_overwrite;
+        false
+    }
+
     pub fn edit(&mut self, edit_op: EditOp) -> ReRenderContent {
+        self.clear_carets();
+        let before = self.cursor_state();
         self.process(&edit_op);
-        self.undo_stack.push_front(edit_op);
-        self.redo_stack.clear();
+        self.history.push(edit_op, before, self.cursor_state());
         true
     }
 
+    pub fn edit_and_place(&mut self, edit_op: EditOp, cursor: PosInDocument, selection_pos: Option<PosInDocument>) -> ReRenderContent {
+        self.clear_carets();
+        let before = self.cursor_state();
+        self.process(&edit_op);
+        self.restore_cursor_state((cursor, selection_pos));
+        self.history.push(edit_op, before, self.cursor_state());
+        true
+    }
+
+    pub fn edit_carets(&mut self, edit_ops: Vec<Option<EditOp>>) -> ReRenderContent {
+        let before = self.cursor_state();
+        let mut positions: Vec<PosInDocument> = iter::once(self.cursor.pos())
+            .chain(self.carets.iter().map(|caret| caret.cursor.pos()))
+            .collect();
+
+        let mut order: Vec<usize> = (0..positions.len()).collect();
+        order.sort_by_key(|&idx| Reverse(edit_ops[idx].as_ref().map(EditOp::from).unwrap_or(positions[idx])));
+
+        let mut processed = Vec::new();
+        for (i, &idx) in order.iter().enumerate() {
+            let Some(edit_op) = edit_ops[idx].as_ref() else { continue };
+
+            positions[idx] = self.apply(edit_op);
+            for &below in &order[..i] {
+                positions[below] = caret::shift(positions[below], edit_op);
+            }
+            processed.push(edit_op.clone());
+        }
+
+        self.changes += processed.len();
+        self.carets = positions[1..].iter().map(|&pos| Caret::new(pos, None)).collect();
+        self.column = None;
+        self.place_cursor(Cursor::from(positions[0]), false);
+        self.dedup_carets();
+
+        let after = self.cursor_state();
+        self.history.begin_group();
+        processed.into_iter().for_each(|edit_op| self.history.push(edit_op, before, after));
+        self.history.end_group();
+        true
+    }
+
+    pub fn begin_group(&mut self) {
+        self.history.begin_group();
+    }
+
+    pub fn end_group(&mut self) {
+        self.history.end_group();
+    }
+
     fn process(&mut self, op: &EditOp) {
-        let next_pos = edit::process(&mut self.content, &op);
-        self.move_cursor(Cursor::from(next_pos), false);
+        let next_pos = self.apply(op);
+        self.changes += 1;
+        self.place_cursor(Cursor::from(next_pos), false);
+    }
+
+    fn apply(&mut self, edit_op: &EditOp) -> PosInDocument {
+        self.highlighter.invalidate(edit_op.from().0);
+        edit::process(&mut self.content, edit_op)
+    }
+
+    pub fn set_language(&mut self, language: Option<&'static Language>) {
+        self.highlighter = Highlighter::new(language);
+        self.brackets_at = None;
+    }
+
+    pub fn language(&self) -> Option<&'static Language> {
+        self.highlighter.language()
+    }
+
+    pub fn update_highlighting(&mut self) -> ReRenderContent {
+        self.highlighter.update(&self.content, self.viewport.top + self.viewport.height as usize);
+
+        let brackets_at = Some((self.cursor.pos(), self.changes));
+        if mem::replace(&mut self.brackets_at, brackets_at) == brackets_at {
+            return false;
+        }
+        let brackets = self.match_brackets();
+        mem::replace(&mut self.brackets, brackets) != brackets
+    }
+
+    fn match_brackets(&mut self) -> Option<(PosInDocument, PosInDocument)> {
+        let cursor = self.cursor.pos();
+        let Self { content, highlighter, .. } = self;
+        let mut is_code = |pos| !matches!(highlighter.kind_at(content, pos), Some(TokenKind::Comment | TokenKind::String));
+
+        let bracket = brackets::bracket_next_to(content, cursor, &mut is_code)?;
+        brackets::find_match(content, bracket, &mut is_code).map(|partner| (bracket, partner))
+    }
+
+    pub fn brackets(&self) -> Option<(PosInDocument, PosInDocument)> {
+        self.brackets
+    }
+
+    pub fn jump_to_bracket(&mut self) -> ReRenderContent {
+        match self.match_brackets() {
+            Some((_, partner)) => self.move_cursor(Cursor::from(partner), false),
+            None => false
+        }
+    }
+
+    pub fn tokens(&self, row: usize) -> &[Token] {
+        self.highlighter.tokens(row)
+    }
+
+    pub fn replace_content(&mut self, text: &str) -> ReRenderContent {
+        self.edit_in_place(EditOp::replace(&self.content, (0, 0), self.content.last_line_end(), text))
+    }
+
+    pub fn clean_up_whitespace(&mut self, trim_trailing: bool, final_newline: bool) -> ReRenderContent {
+        let mut lines: Vec<&str> = self.content.lines.iter()
+            .map(|line| if trim_trailing { line[..].trim_end() } else { &line[..] })
+            .collect();
+        if final_newline && lines.last() != Some(&"") {
+            lines.push("");
+        }
+
+        let is_changed = lines.iter().copied().ne(self.content.lines.iter().map(|line| &line[..]));
+        if is_changed {
+            let text = lines.join("\n");
+            self.replace_content(&text);
+        }
+        is_changed
+    }
+
+    fn edit_in_place(&mut self, edit_op: EditOp) -> ReRenderContent {
+        let (cursor_pos, (top, left)) = (self.cursor.pos(), self.viewport.pos());
+
+        self.edit(edit_op);
+
+        let cursor = Cursor::from((0, 0));
+        self.viewport.scroll(top, left);
+        self.move_cursor(cursor.move_to(&self.content, cursor_pos).unwrap_or(cursor), false)
     }
 
     pub fn undo(&mut self) -> ReRenderContent {
-        self.undo_stack.pop_front()
-            .map(|edit_op| {
-                self.process(&edit_op.inverse());
-                self.redo_stack.push_front(edit_op)
-            })
-            .is_some()
+        self.travel(self.history.undo_target())
     }
 
     pub fn redo(&mut self) -> ReRenderContent {
-        self.redo_stack.pop_front()
-            .map(|edit_op| {
-                self.process(&edit_op);
-                self.undo_stack.push_front(edit_op)
-            })
-            .is_some()
+        self.travel(self.history.redo_target())
+    }
+
+    pub fn travel_earlier(&mut self) -> ReRenderContent {
+        self.travel(self.history.earlier_target())
+    }
+
+    pub fn travel_later(&mut self) -> ReRenderContent {
+        self.travel(self.history.later_target())
+    }
+
+    fn travel(&mut self, target: Option<usize>) -> ReRenderContent {
+        let Some(target) = target else { return false };
+
+        self.clear_carets();
+        for (edit_ops, cursor_state) in self.history.travel(target) {
+            edit_ops.iter().for_each(|edit_op| self.process(edit_op));
+            self.restore_cursor_state(cursor_state);
+        }
+        true
+    }
+
+    pub fn open_undo_browser(&mut self) -> ReRenderContent {
+        self.undo_browser = Some(self.history.current());
+        true
+    }
+
+    pub fn move_undo_browser(&mut self, delta: isize) -> ReRenderContent {
+        let last = self.history.nodes().len() - 1;
+        self.undo_browser = self.undo_browser.map(|selected| selected.saturating_add_signed(delta).min(last));
+        true
+    }
+
+    pub fn close_undo_browser(&mut self, accept: bool) -> ReRenderContent {
+        let selected = self.undo_browser.take();
+        if accept {
+            self.travel(selected);
+        }
+        true
+    }
+
+    pub fn history(&self) -> &UndoHistory {
+        &self.history
+    }
+
+    pub fn set_history(&mut self, history: UndoHistory) {
+        self.history = history;
+        self.history.set_budget(self.settings.undo_budget);
+    }
+
+    pub fn apply_settings(&mut self, settings: Settings) {
+        self.indent = self.indent_override.or(self.content.indent).unwrap_or(settings.indent());
+        self.history.set_budget(settings.undo_budget);
+        self.settings = settings;
+    }
+
+    pub fn is_indent_overridden(&self) -> bool {
+        self.indent_override.is_some()
+    }
+
+    fn cursor_state(&self) -> CursorState {
+        (self.cursor.pos(), self.selection_pos)
+    }
+
+    fn restore_cursor_state(&mut self, (cursor_pos, selection_pos): CursorState) -> ReRenderContent {
+        self.selection_pos = selection_pos;
+        self.cursor = Cursor::from(cursor_pos);
+
+        if let Some(scroll_to) = self.viewport.scroll_into_view(cursor_pos) {
+            self.scroll(scroll_to);
+        }
+        true
+    }
+
+
+    pub fn open_find(&mut self) -> ReRenderContent {
+        self.search.scope = None;
+        self.open_search(PromptKind::Find, "Find: ")
+    }
+
+    pub fn open_replace(&mut self) -> ReRenderContent {
+        self.search.scope = self.selection().filter(|(from, to)| from.0 != to.0);
+        self.open_search(PromptKind::Replace, "Replace: ")
+    }
+
+    fn open_search(&mut self, kind: PromptKind, label: &str) -> ReRenderContent {
+        self.clear_carets();
+        let query = match self.selection() {
+            Some((from, to)) if from.0 == to.0 => self.content.selected_text(from, to),
+            _ => self.search.query.clone()
+        };
+
+        self.search_origin = Some((self.cursor.pos(), self.selection_pos));
+        self.prompt = Some(Prompt::new(kind, label, &query));
+        self.search_incremental()
+    }
+
+    pub fn open_replace_with(&mut self) -> ReRenderContent {
+        self.prompt = Some(Prompt::new(PromptKind::ReplaceWith, "Replace with: ", &self.search.replace_with));
+        true
+    }
+
+    pub fn search_incremental(&mut self) -> ReRenderContent {
+        let Some((cursor_pos, selection_pos)) = self.search_origin else { return false };
+
+        self.search.query = self.prompt_text();
+        self.search.update(&self.content);
+
+        let origin = selection_pos.filter(|pos| pos.is_before(&cursor_pos)).unwrap_or(cursor_pos);
+        match self.search.next(origin, None) {
+            Some((from, to)) => self.select(from, to),
+            None => self.restore_search_origin()
+        }
+    }
+
+    pub fn toggle_search_option(&mut self, toggle: fn(&mut SearchOptions)) -> ReRenderContent {
+        toggle(&mut self.search.options);
+        self.search_incremental()
+    }
+
+    pub fn find_next(&mut self, forward: bool) -> ReRenderContent {
+        if self.search.query.is_empty() {
+            return self.open_find();
+        }
+        if self.prompt.is_none() {
+            self.search.update(&self.content);
+        }
+
+        let selection = self.selection();
+        let pos = selection.map(|(from, _)| from).unwrap_or(self.cursor.pos());
+        let found = if forward { self.search.next(pos, selection) } else { self.search.prev(pos) };
+
+        found.map(|(from, to)| self.select(from, to)).unwrap_or(false)
+    }
+
+    pub fn update_replace_with(&mut self) -> ReRenderContent {
+        self.search.replace_with = self.prompt_text();
+        false
+    }
+
+    pub fn replace_next(&mut self) -> ReRenderContent {
+        let Some(idx) = self.search.index_of(self.selection()) else { return self.find_next(true) };
+
+        let (from, to) = self.search.matches[idx];
+        let replacement = self.search.replacement(&self.content, (from, to));
+        let edit_op = EditOp::replace(&self.content, from, to, &replacement);
+        self.search.scope = self.search.scope.map(|(scope_from, scope_to)| (scope_from, caret::shift(scope_to, &edit_op)));
+        self.edit(edit_op);
+        self.search.update(&self.content);
+
+        if let Some((from, to)) = self.search.next(self.cursor.pos(), None) {
+            self.select(from, to);
+        }
+        true
+    }
+
+    pub fn replace_all(&mut self) -> ReRenderContent {
+        let Some(edit_op) = self.search.replace_all(&self.content) else { return false };
+
+        self.close_prompt(true);
+        self.edit_in_place(edit_op)
+    }
+
+    pub fn close_prompt(&mut self, accept: bool) -> ReRenderContent {
+        if !accept {
+            self.restore_search_origin();
+        }
+        self.prompt = None;
+        self.search_origin = None;
+        self.search.scope = None;
+        true
+    }
+
+    fn prompt_text(&self) -> String {
+        self.prompt.as_ref().map(|prompt| prompt.text().to_string()).unwrap_or_default()
+    }
+
+    fn restore_search_origin(&mut self) -> ReRenderContent {
+        let Some((cursor_pos, selection_pos)) = self.search_origin else { return false };
+
+        self.selection_pos = selection_pos;
+        self.move_cursor(Cursor::from(cursor_pos), selection_pos.is_some());
+        true
     }
 }
@@ -0,0 +1,442 @@
+use std::{cmp::Ordering, collections::HashSet};
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Transform {
+    Upper,
+    Lower,
+    Title,
+    Camel,
+    Snake,
+    Sort,
+    SortNumeric,
+    SortNoCase,
+    Unique,
+    Reverse,
+    Base64Encode,
+    Base64Decode,
+    UrlEncode,
+    UrlDecode,
+    JsonEscape,
+    JsonUnescape,
+    HtmlEncode,
+    HtmlDecode,
+}
+
+pub const TRANSFORMS: [(&str, Transform); 18] = [
+    ("upper", Transform::Upper),
+    ("lower", Transform::Lower),
+    ("title", Transform::Title),
+    ("camel", Transform::Camel),
+    ("snake", Transform::Snake),
+    ("sort", Transform::Sort),
+    ("sort-numeric", Transform::SortNumeric),
+    ("sort-nocase", Transform::SortNoCase),
+    ("unique", Transform::Unique),
+    ("reverse", Transform::Reverse),
+    ("base64-encode", Transform::Base64Encode),
+    ("base64-decode", Transform::Base64Decode),
+    ("url-encode", Transform::UrlEncode),
+    ("url-decode", Transform::UrlDecode),
+    ("json-escape", Transform::JsonEscape),
+    ("json-unescape", Transform::JsonUnescape),
+    ("html-encode", Transform::HtmlEncode),
+    ("html-decode", Transform::HtmlDecode),
+];
+
+pub fn parse(name: &str) -> Option<Transform> {
+    let name = name.trim().to_lowercase();
+    TRANSFORMS.iter().find(|(n, _)| *n == name).map(|&(_, transform)| transform)
+}
+
+pub fn names_starting_with(prefix: &str) -> Vec<&'static str> {
+    let prefix = prefix.trim().to_lowercase();
+    TRANSFORMS.iter().map(|&(name, _)| name).filter(|name| name.starts_with(&prefix)).collect()
+}
+
+pub fn apply(transform: Transform, text: &str) -> Option<String> {
+    match transform {
+        Transform::Upper => Some(text.to_uppercase()),
+        Transform::Lower => Some(text.to_lowercase()),
+        Transform::Title => Some(map_words(text, capitalize)),
+        Transform::Camel => Some(map_words(text, camel)),
+        Transform::Snake => Some(map_words(text, snake)),
+        Transform::Sort => Some(sort_lines(text, natural_cmp)),
+        Transform::SortNumeric => Some(sort_lines(text, |a, b| leading_number(a).total_cmp(&leading_number(b)))),
+        Transform::SortNoCase => Some(sort_lines(text, |a, b| a.to_lowercase().cmp(&b.to_lowercase()))),
+        Transform::Unique => {
+            let mut seen = HashSet::new();
+            Some(map_lines(text, |lines| lines.into_iter().filter(|line| seen.insert(*line)).collect()))
+        },
+        Transform::Reverse => Some(map_lines(text, |lines| lines.into_iter().rev().collect())),
+        Transform::Base64Encode => Some(base64_encode(text.as_bytes())),
+        Transform::Base64Decode => base64_decode(text).and_then(|bytes| String::from_utf8(bytes).ok()),
+        Transform::UrlEncode => Some(url_encode(text)),
+        Transform::UrlDecode => url_decode(text),
+        Transform::JsonEscape => Some(json_escape(text)),
+        Transform::JsonUnescape => json_unescape(text),
+        Transform::HtmlEncode => Some(html_encode(text)),
+        Transform::HtmlDecode => Some(html_decode(text)),
+    }
+}
+
+
+fn map_words<F>(text: &str, f: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    let mut result = String::new();
+    let mut word = String::new();
+
+    for c in text.chars().chain(['\0']) {
+        if c.is_alphanumeric() || c == '_' || c == '-' {
+            word.push(c);
+            continue;
+        }
+        if !word.is_empty() {
+            result.push_str(&f(&word));
+            word.clear();
+        }
+        if c != '\0' {
+            result.push(c);
+        }
+    }
+    result
+}
+
+fn split_words(identifier: &str) -> Vec<String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut prev: Option<char> = None;
+    let mut chars = identifier.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '_' || c == '-' {
+            prev = None;
+            continue;
+        }
+        let is_boundary = match prev {
+            None => true,
+            Some(p) if p.is_uppercase() => c.is_uppercase() && chars.peek().is_some_and(|next| next.is_lowercase()),
+            Some(_) => c.is_uppercase(),
+        };
+        if is_boundary {
+            words.push(String::new());
+        }
+        words.last_mut().unwrap().push(c);
+        prev = Some(c);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+fn camel(word: &str) -> String {
+    split_words(word).iter().enumerate()
+        .map(|(i, word)| if i == 0 { word.to_lowercase() } else { capitalize(word) })
+        .collect()
+}
+
+fn snake(word: &str) -> String {
+    split_words(word).iter().map(|word| word.to_lowercase()).collect::<Vec<_>>().join("_")
+}
+
+
+fn sort_lines<F>(text: &str, cmp: F) -> String
+where
+    F: Fn(&str, &str) -> Ordering,
+{
+    map_lines(text, |mut lines| {
+        lines.sort_by(|a, b| cmp(a, b));
+        lines
+    })
+}
+
+fn map_lines<'a, F>(text: &'a str, f: F) -> String
+where
+    F: FnOnce(Vec<&'a str>) -> Vec<&'a str>,
+{
+    let (text, line_end) = match text.strip_suffix('\n') {
+        Some(text) => (text, "\n"),
+        None => (text, ""),
+    };
+    format!("{}{}", f(text.split('\n').collect()).join("\n"), line_end)
+}
+
+fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a, b);
+
+    loop {
+        let (a_digits, b_digits) = (digits_len(a), digits_len(b));
+        let ordering = match (a_digits, b_digits) {
+            (0, _) | (_, 0) => match (a.chars().next(), b.chars().next()) {
+                (Some(x), Some(y)) => x.cmp(&y),
+                (x, y) => return x.cmp(&y),
+            },
+            _ => {
+                let (x, y) = (a[..a_digits].trim_start_matches('0'), b[..b_digits].trim_start_matches('0'));
+                x.len().cmp(&y.len()).then(x.cmp(y))
+            },
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+
+        let (a_len, b_len) = match a_digits.min(b_digits) {
+            0 => (a.chars().next().map_or(0, char::len_utf8), b.chars().next().map_or(0, char::len_utf8)),
+            _ => (a_digits, b_digits),
+        };
+        (a, b) = (&a[a_len..], &b[b_len..]);
+    }
+}
+
+fn digits_len(text: &str) -> usize {
+    text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len())
+}
+
+fn leading_number(line: &str) -> f64 {
+    let line = line.trim_start();
+    let end = line.char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || (i == 0 && (c == '-' || c == '+'))))
+        .map(|(i, _)| i)
+        .unwrap_or(line.len());
+    line[..end].parse().unwrap_or(f64::NEG_INFINITY)
+}
+
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut result = String::new();
+
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => result.push(BASE64[(n >> (18 - 6 * i) & 0x3F) as usize] as char),
+                false => result.push('='),
+            }
+        }
+    }
+    result
+}
+
+fn base64_decode(text: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    let (mut n, mut bits) = (0u32, 0);
+
+    for c in text.bytes().filter(|c| !c.is_ascii_whitespace()).take_while(|&c| c != b'=') {
+        n = n << 6 | BASE64.iter().position(|&b| b == c)? as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((n >> bits) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+
+fn url_encode(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+fn url_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::new();
+    let mut iter = text.bytes();
+
+    while let Some(b) = iter.next() {
+        match b {
+            b'%' => {
+                let hex = [iter.next()?, iter.next()?];
+                bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+            },
+            _ => bytes.push(b),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+
+fn json_escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '"' => "\\\"".to_string(),
+            '\\' => "\\\\".to_string(),
+            '\n' => "\\n".to_string(),
+            '\r' => "\\r".to_string(),
+            '\t' => "\\t".to_string(),
+            c if (c as u32) < 0x20 => format!("\\u{:04x}", c as u32),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+fn json_unescape(text: &str) -> Option<String> {
+    let mut result = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => result.push('\n'),
+            'r' => result.push('\r'),
+            't' => result.push('\t'),
+            'b' => result.push('\u{8}'),
+            'f' => result.push('\u{c}'),
+            'u' => {
+                let mut code = hex4(&mut chars)?;
+                if (0xD800..0xDC00).contains(&code) {
+                    if chars.next()? != '\\' || chars.next()? != 'u' { return None }
+                    code = 0x10000 + ((code - 0xD800) << 10) + (hex4(&mut chars)?.checked_sub(0xDC00)?);
+                }
+                result.push(char::from_u32(code)?);
+            },
+            c => result.push(c),
+        }
+    }
+    Some(result)
+}
+
+fn hex4(chars: &mut std::str::Chars) -> Option<u32> {
+    let hex: String = chars.take(4).collect();
+    if hex.len() == 4 { u32::from_str_radix(&hex, 16).ok() } else { None }
+}
+
+
+const HTML_ENTITIES: [(char, &str); 5] = [('&', "&amp;"), ('<', "&lt;"), ('>', "&gt;"), ('"', "&quot;"), ('\'', "&#39;")];
+
+fn html_encode(text: &str) -> String {
+    text.chars()
+        .map(|c| match HTML_ENTITIES.iter().find(|(ch, _)| *ch == c) {
+            Some((_, entity)) => entity.to_string(),
+            None => c.to_string(),
+        })
+        .collect()
+}
+
+fn html_decode(text: &str) -> String {
+    let mut result = String::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        result.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| Some((html_entity(&rest[1..end])?, end)));
+        match decoded {
+            Some((c, end)) => {
+                result.push(c);
+                rest = &rest[end + 1..];
+            },
+            None => {
+                result.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn html_entity(name: &str) -> Option<char> {
+    match name {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some('\u{a0}'),
+        _ => match name.strip_prefix('#')? {
+            hex if hex.starts_with(['x', 'X']) => char::from_u32(u32::from_str_radix(&hex[1..], 16).ok()?),
+            dec => char::from_u32(dec.parse().ok()?),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::{*, Transform::*};
+
+    fn apply_ok(transform: Transform, text: &str) -> String {
+        apply(transform, text).unwrap()
+    }
+
+    #[test]
+    fn parse_names() {
+        assert_eq!(parse(" Sort-Numeric "), Some(SortNumeric));
+        assert_eq!(parse("sideways"), None);
+        assert_eq!(names_starting_with("sort"), vec!["sort", "sort-numeric", "sort-nocase"]);
+    }
+
+    #[test]
+    fn case_transforms() {
+        assert_eq!(apply_ok(Title, "hello wORLD, again"), "Hello World, Again");
+        assert_eq!(apply_ok(Camel, "user_name = first-name + LastName"), "userName = firstName + lastName");
+        assert_eq!(apply_ok(Snake, "userName = HTTPServer"), "user_name = http_server");
+        assert_eq!(apply_ok(Camel, "parse_URL"), "parseUrl");
+        assert_eq!(apply_ok(Upper, "straße"), "STRASSE");
+    }
+
+    #[test]
+    fn sort_transforms() {
+        assert_eq!(apply_ok(Sort, "item10\nitem9\nItem1"), "Item1\nitem9\nitem10");
+        assert_eq!(apply_ok(SortNumeric, "10 b\n-2 a\nnone\n3.5"), "none\n-2 a\n3.5\n10 b");
+        assert_eq!(apply_ok(SortNoCase, "b\nA\na\nC"), "A\na\nb\nC");
+    }
+
+    #[test]
+    fn unique_and_reverse() {
+        assert_eq!(apply_ok(Unique, "a\nb\na\nc\nb"), "a\nb\nc");
+        assert_eq!(apply_ok(Reverse, "1\n2\n3"), "3\n2\n1");
+    }
+
+    #[test]
+    fn line_transforms_keep_trailing_newline() {
+        assert_eq!(apply_ok(Sort, "b\nc\na\n"), "a\nb\nc\n");
+        assert_eq!(apply_ok(Reverse, "b\nc\na\n"), "a\nc\nb\n");
+        assert_eq!(apply_ok(Unique, "a\n\na\n"), "a\n\n");
+    }
+
+    #[test]
+    fn base64_round_trip() {
+        assert_eq!(apply_ok(Base64Encode, "picow"), "cGljb3c=");
+        assert_eq!(apply_ok(Base64Encode, "pi"), "cGk=");
+        assert_eq!(apply_ok(Base64Decode, "cGljb3c="), "picow");
+        assert_eq!(apply(Base64Decode, "c*"), None);
+    }
+
+    #[test]
+    fn url_round_trip() {
+        assert_eq!(apply_ok(UrlEncode, "a b&c=ü"), "a%20b%26c%3D%C3%BC");
+        assert_eq!(apply_ok(UrlDecode, "a%20b%26c%3D%C3%BC"), "a b&c=ü");
+        assert_eq!(apply(UrlDecode, "%2"), None);
+    }
+
+    #[test]
+    fn json_round_trip() {
+        assert_eq!(apply_ok(JsonEscape, "say \"hi\"\n\tC:\\"), r#"say \"hi\"\n\tC:\\"#);
+        assert_eq!(apply_ok(JsonUnescape, r#"say \"hi\"\n\u00e9 \ud83d\ude00"#), "say \"hi\"\né 😀");
+        assert_eq!(apply(JsonUnescape, r"\u12"), None);
+    }
+
+    #[test]
+    fn html_round_trip() {
+        assert_eq!(apply_ok(HtmlEncode, "<a href=\"x\">R&D</a>"), "&lt;a href=&quot;x&quot;&gt;R&amp;D&lt;/a&gt;");
+        assert_eq!(apply_ok(HtmlDecode, "&lt;b&gt; &#65;&#x42; & &unknown;"), "<b> AB & &unknown;");
+    }
+}
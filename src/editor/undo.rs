@@ -0,0 +1,621 @@
+use std::{mem, time::{Duration, Instant, SystemTime}};
+
+use super::{edit::EditOp, pos::PosInDocument, row::{Row, RowVecExt}};
+
+const TYPING_PAUSE: Duration = Duration::from_secs(1);
+pub const DEFAULT_BUDGET: usize = 64 * 1024 * 1024;
+
+
+pub type CursorState = (PosInDocument, Option<PosInDocument>);
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum UndoOp {
+    Insert { from: PosInDocument, text: String },
+    Delete { from: PosInDocument, text: String },
+    Replace { from: PosInDocument, inserted: String, deleted: String },
+}
+
+#[derive(PartialEq, Debug)]
+pub struct UndoStep {
+    pub ops: Vec<UndoOp>,
+    pub before: CursorState,
+    pub after: CursorState,
+}
+
+pub struct UndoNode {
+    pub step: UndoStep,
+    pub at: SystemTime,
+    parent: usize,
+    children: Vec<usize>,
+    redo_child: Option<usize>,
+}
+
+pub type Travel = Vec<(Vec<EditOp>, CursorState)>;
+
+pub struct UndoHistory {
+    nodes: Vec<UndoNode>,
+    current: usize,
+    group_depth: usize,
+    group_started: bool,
+    typing: Option<Typing>,
+    size: usize,
+    budget: usize,
+    is_truncated: bool,
+}
+
+#[derive(Clone, Copy)]
+struct Typing {
+    kind: TypingKind,
+    from: PosInDocument,
+    to: PosInDocument,
+    is_space: bool,
+    at: Instant,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum TypingKind {
+    Insert,
+    Delete,
+    Overwrite,
+}
+
+impl Default for UndoHistory {
+    fn default() -> Self {
+        let root = UndoNode::new(UndoStep { ops: Vec::new(), before: ((0, 0), None), after: ((0, 0), None) }, 0);
+        Self {
+            nodes: vec![root], current: 0,
+            group_depth: 0, group_started: false, typing: None,
+            size: 0, budget: DEFAULT_BUDGET, is_truncated: false,
+        }
+    }
+}
+
+impl UndoNode {
+    pub fn new(step: UndoStep, parent: usize) -> Self {
+        Self { step, at: SystemTime::now(), parent, children: Vec::new(), redo_child: None }
+    }
+
+    pub fn parent(&self) -> usize {
+        self.parent
+    }
+}
+
+impl UndoHistory {
+    pub fn restore(mut nodes: Vec<UndoNode>, current: usize) -> Option<Self> {
+        let is_tree = !nodes.is_empty() && nodes.iter().enumerate().skip(1).all(|(idx, node)| node.parent < idx);
+        if !is_tree || current >= nodes.len() {
+            return None;
+        }
+
+        for idx in 1..nodes.len() {
+            let parent = nodes[idx].parent;
+            nodes[parent].children.push(idx);
+            nodes[parent].redo_child = Some(idx);
+        }
+
+        let size = size(&nodes);
+        Some(Self { nodes, current, size, ..Default::default() })
+    }
+
+    pub fn set_budget(&mut self, budget: usize) {
+        self.budget = budget;
+        self.compact();
+    }
+
+    pub fn is_truncated(&self) -> bool {
+        self.is_truncated
+    }
+
+    pub fn push(&mut self, edit_op: EditOp, before: CursorState, after: CursorState) {
+        let typing = typing(&edit_op);
+        let can_merge = self.current != 0 && self.nodes[self.current].children.is_empty();
+        let merge = can_merge && if self.group_depth > 0 {
+            self.group_started
+        } else {
+            matches!((self.typing, typing), (Some(prev), Some(next)) if prev.continues_with(&next))
+        };
+
+        let op: UndoOp = (&edit_op).into();
+
+        if merge {
+            let node = &mut self.nodes[self.current];
+            self.size -= node.step.size();
+            node.step.push(op);
+            node.step.after = after;
+            node.at = SystemTime::now();
+            self.size += node.step.size();
+        } else {
+            let idx = self.nodes.len();
+            let step = UndoStep { ops: vec![op], before, after };
+            self.size += step.size();
+            self.nodes.push(UndoNode::new(step, self.current));
+            self.nodes[self.current].children.push(idx);
+            self.nodes[self.current].redo_child = Some(idx);
+            self.current = idx;
+        }
+
+        self.group_started = self.group_depth > 0;
+        self.typing = if self.group_depth > 0 { None } else { typing };
+        self.compact();
+    }
+
+    fn compact(&mut self) {
+        while self.size > self.budget && self.current != 0 && self.nodes[self.current].parent != 0 {
+            self.drop_oldest();
+        }
+    }
+
+    fn drop_oldest(&mut self) {
+        let path = self.ancestors(self.current);
+        let new_root = path[path.len() - 2];
+
+        let mut new_idx = vec![None; self.nodes.len()];
+        let mut kept = 0;
+        for idx in new_root..self.nodes.len() {
+            if idx == new_root || new_idx[self.nodes[idx].parent].is_some() {
+                new_idx[idx] = Some(kept);
+                kept += 1;
+            }
+        }
+
+        let nodes = mem::take(&mut self.nodes);
+        self.nodes = nodes.into_iter().enumerate()
+            .filter(|&(idx, _)| new_idx[idx].is_some())
+            .map(|(_, mut node)| {
+                node.parent = new_idx[node.parent].unwrap_or(0);
+                node.children = node.children.iter().filter_map(|&child| new_idx[child]).collect();
+                node.redo_child = node.redo_child.and_then(|child| new_idx[child]);
+                node
+            })
+            .collect();
+
+        self.nodes[0].parent = 0;
+        self.nodes[0].step.ops.clear();
+        self.current = new_idx[self.current].unwrap_or(0);
+        self.size = size(&self.nodes);
+        self.is_truncated = true;
+    }
+
+    pub fn begin_group(&mut self) {
+        if self.group_depth == 0 {
+            self.group_started = false;
+        }
+        self.group_depth += 1;
+        self.typing = None;
+    }
+
+    pub fn end_group(&mut self) {
+        self.group_depth = self.group_depth.saturating_sub(1);
+    }
+
+    pub fn break_coalescing(&mut self) {
+        self.typing = None;
+    }
+
+    pub fn nodes(&self) -> &[UndoNode] {
+        &self.nodes
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn undo_target(&self) -> Option<usize> {
+        (self.current != 0).then(|| self.nodes[self.current].parent)
+    }
+
+    pub fn redo_target(&self) -> Option<usize> {
+        self.nodes[self.current].redo_child
+    }
+
+    pub fn earlier_target(&self) -> Option<usize> {
+        self.current.checked_sub(1)
+    }
+
+    pub fn later_target(&self) -> Option<usize> {
+        (self.current + 1 < self.nodes.len()).then(|| self.current + 1)
+    }
+
+    pub fn travel(&mut self, target: usize) -> Travel {
+        self.typing = None;
+        let (up, down) = self.path(self.current, target);
+        let mut travel = Travel::new();
+
+        for node in up {
+            let UndoStep { ref ops, before, .. } = self.nodes[node].step;
+            travel.push((ops.iter().rev().map(|op| op.edit_op().inverse()).collect(), before));
+
+            let parent = self.nodes[node].parent;
+            self.nodes[parent].redo_child = Some(node);
+        }
+
+        for node in down {
+            let UndoStep { ref ops, after, .. } = self.nodes[node].step;
+            travel.push((ops.iter().map(UndoOp::edit_op).collect(), after));
+
+            let parent = self.nodes[node].parent;
+            self.nodes[parent].redo_child = Some(node);
+        }
+
+        self.current = target;
+        travel
+    }
+
+    fn path(&self, from: usize, to: usize) -> (Vec<usize>, Vec<usize>) {
+        let (from_path, to_path) = (self.ancestors(from), self.ancestors(to));
+        let common = from_path.iter().find(|node| to_path.contains(node)).copied().unwrap_or(0);
+
+        let up = from_path.into_iter().take_while(|&node| node != common).collect();
+        let mut down: Vec<usize> = to_path.into_iter().take_while(|&node| node != common).collect();
+        down.reverse();
+
+        (up, down)
+    }
+
+    fn ancestors(&self, mut node: usize) -> Vec<usize> {
+        let mut ancestors = vec![node];
+        while node != 0 {
+            node = self.nodes[node].parent;
+            ancestors.push(node);
+        }
+        ancestors
+    }
+}
+
+impl UndoStep {
+    fn push(&mut self, op: UndoOp) {
+        let is_merged = self.ops.last_mut().is_some_and(|last| last.merge(&op));
+        if !is_merged {
+            self.ops.push(op);
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        mem::size_of::<UndoNode>() + self.ops.iter().map(UndoOp::size).sum::<usize>()
+    }
+}
+
+impl From<&EditOp> for UndoOp {
+    fn from(edit_op: &EditOp) -> Self {
+        match edit_op {
+            EditOp::Insert { from, lines } => Self::Insert { from: *from, text: lines.join("\n") },
+            EditOp::Delete { from, lines } => Self::Delete { from: *from, text: lines.join("\n") },
+            EditOp::Replace { from, inserted_lines, deleted_lines } =>
+                Self::Replace { from: *from, inserted: inserted_lines.join("\n"), deleted: deleted_lines.join("\n") },
+        }
+    }
+}
+
+impl UndoOp {
+    pub fn edit_op(&self) -> EditOp {
+        match self {
+            Self::Insert { from, text } => EditOp::Insert { from: *from, lines: rows(text) },
+            Self::Delete { from, text } => EditOp::Delete { from: *from, lines: rows(text) },
+            Self::Replace { from, inserted, deleted } =>
+                EditOp::Replace { from: *from, inserted_lines: rows(inserted), deleted_lines: rows(deleted) },
+        }
+    }
+
+    pub fn from(&self) -> PosInDocument {
+        match self {
+            Self::Insert { from, .. } | Self::Delete { from, .. } | Self::Replace { from, .. } => *from
+        }
+    }
+
+    fn size(&self) -> usize {
+        mem::size_of::<Self>() + match self {
+            Self::Insert { text, .. } | Self::Delete { text, .. } => text.len(),
+            Self::Replace { inserted, deleted, .. } => inserted.len() + deleted.len(),
+        }
+    }
+
+    fn merge(&mut self, next: &UndoOp) -> bool {
+        match (self, next) {
+            (Self::Insert { from, text }, Self::Insert { from: next_from, text: next_text }) => {
+                let is_adjacent = *next_from == end(*from, text);
+                if is_adjacent {
+                    text.push_str(next_text);
+                }
+                is_adjacent
+            },
+            (Self::Delete { from, text }, Self::Delete { from: next_from, text: next_text }) => {
+                if end(*next_from, next_text) == *from {
+                    *from = *next_from;
+                    text.insert_str(0, next_text);
+                    true
+                } else if next_from == from {
+                    text.push_str(next_text);
+                    true
+                } else {
+                    false
+                }
+            },
+            (Self::Replace { from, inserted, deleted }, Self::Replace { from: next_from, inserted: next_inserted, deleted: next_deleted }) => {
+                let is_adjacent = *next_from == end(*from, inserted);
+                if is_adjacent {
+                    inserted.push_str(next_inserted);
+                    deleted.push_str(next_deleted);
+                }
+                is_adjacent
+            },
+            _ => false
+        }
+    }
+}
+
+fn size(nodes: &[UndoNode]) -> usize {
+    nodes[1..].iter().map(|node| node.step.size()).sum()
+}
+
+fn rows(text: &str) -> Vec<Row> {
+    text.split('\n').map(Row::new).collect()
+}
+
+fn end((row, col): PosInDocument, text: &str) -> PosInDocument {
+    match text.rsplit_once('\n') {
+        Some((before, last)) => (row + before.matches('\n').count() + 1, Row::new(last).len()),
+        None => (row, col + Row::new(text).len()),
+    }
+}
+
+impl Typing {
+    fn continues_with(&self, next: &Typing) -> bool {
+        let is_adjacent = match self.kind {
+            TypingKind::Insert | TypingKind::Overwrite => next.from == self.to,
+            TypingKind::Delete => next.to == self.from || next.from == self.from,
+        };
+
+        let starts_word = self.is_space && !next.is_space;
+
+        self.kind == next.kind && is_adjacent && !starts_word
+            && next.at.duration_since(self.at) < TYPING_PAUSE
+    }
+}
+
+fn typing(edit_op: &EditOp) -> Option<Typing> {
+    let (kind, from, lines) = match edit_op {
+        EditOp::Insert { from, lines } => (TypingKind::Insert, *from, lines),
+        EditOp::Delete { from, lines } => (TypingKind::Delete, *from, lines),
+        EditOp::Replace { from, inserted_lines, deleted_lines } if matches!(&deleted_lines[..], [line] if line.len() == 1) =>
+            (TypingKind::Overwrite, *from, inserted_lines),
+        EditOp::Replace { .. } => return None,
+    };
+
+    match &lines[..] {
+        [line] if line.len() == 1 => Some(Typing {
+            kind, from,
+            to: edit_op.to(),
+            is_space: line[..].chars().all(char::is_whitespace),
+            at: Instant::now(),
+        }),
+        _ => None
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use crate::{s, vecr};
+    use super::*;
+
+    fn insert(col: usize, str: &str) -> EditOp {
+        EditOp::Insert { from: (0, col), lines: vecr![str] }
+    }
+
+    fn delete(col: usize, str: &str) -> EditOp {
+        EditOp::Delete { from: (0, col), lines: vecr![str] }
+    }
+
+    fn history(ops: Vec<EditOp>) -> UndoHistory {
+        let mut history = UndoHistory::default();
+        ops.into_iter().for_each(|op| push(&mut history, op));
+        history
+    }
+
+    fn push(history: &mut UndoHistory, op: EditOp) {
+        let to = op.to();
+        history.push(op, ((0, 0), None), (to, None));
+    }
+
+    fn steps(history: &UndoHistory) -> Vec<usize> {
+        history.nodes[1..].iter().rev().map(|node| node.step.ops.len()).collect()
+    }
+
+    fn undo(history: &mut UndoHistory) -> Travel {
+        let target = history.undo_target().unwrap();
+        history.travel(target)
+    }
+
+    fn redo(history: &mut UndoHistory) -> Travel {
+        let target = history.redo_target().unwrap();
+        history.travel(target)
+    }
+
+    #[test]
+    fn typing_merges_into_word() {
+        let history = history(vec![insert(0, "H"), insert(1, "i"), insert(2, " "), insert(3, "y"), insert(4, "o")]);
+        assert_eq!(steps(&history), vec![1, 1]);
+    }
+
+    #[test]
+    fn typing_elsewhere_breaks() {
+        let history = history(vec![insert(0, "H"), insert(5, "i")]);
+        assert_eq!(steps(&history), vec![1, 1]);
+    }
+
+    #[test]
+    fn backspaces_merge() {
+        let history = history(vec![delete(4, "o"), delete(3, "l"), delete(2, "l")]);
+        assert_eq!(steps(&history), vec![1]);
+    }
+
+    #[test]
+    fn insert_then_delete_breaks() {
+        let history = history(vec![insert(0, "H"), delete(0, "H")]);
+        assert_eq!(steps(&history), vec![1, 1]);
+    }
+
+    #[test]
+    fn overwrite_merges_into_word() {
+        let replace = |col, str: &str| EditOp::Replace { from: (0, col), inserted_lines: vecr![str], deleted_lines: vecr!["x"] };
+        let history = history(vec![replace(0, "a"), replace(1, "b"), replace(2, "c"), replace(3, " "), replace(4, "d")]);
+        assert_eq!(steps(&history), vec![1, 1]);
+        assert_eq!(history.nodes[1].step.ops, vec![UndoOp::Replace { from: (0, 0), inserted: s!["abc "], deleted: s!["xxxx"] }]);
+    }
+
+    #[test]
+    fn multi_char_insert_not_merged() {
+        let history = history(vec![insert(0, "H"), insert(1, "ello")]);
+        assert_eq!(steps(&history), vec![1, 1]);
+    }
+
+    #[test]
+    fn break_coalescing() {
+        let mut history = history(vec![insert(0, "H")]);
+        history.break_coalescing();
+        push(&mut history, insert(1, "i"));
+        assert_eq!(steps(&history), vec![1, 1]);
+    }
+
+    #[test]
+    fn group_is_single_step() {
+        let mut history = history(vec![insert(0, "H")]);
+        history.begin_group();
+        push(&mut history, insert(1, "i"));
+        history.begin_group();
+        push(&mut history, insert(9, "Hello"));
+        history.end_group();
+        push(&mut history, delete(0, "H"));
+        history.end_group();
+        push(&mut history, insert(0, "!"));
+        assert_eq!(steps(&history), vec![1, 3, 1]);
+    }
+
+    #[test]
+    fn merged_step_keeps_first_before_and_last_after() {
+        let mut history = UndoHistory::default();
+        history.push(insert(0, "H"), ((0, 0), Some((0, 3))), ((0, 1), None));
+        history.push(insert(1, "i"), ((0, 1), None), ((0, 2), None));
+
+        let step = &history.nodes[history.current].step;
+        assert_eq!((step.before, step.after), (((0, 0), Some((0, 3))), ((0, 2), None)));
+    }
+
+    #[test]
+    fn typed_text_stored_as_single_op() {
+        let history = history(vec![insert(0, "H"), insert(1, "i"), insert(2, " ")]);
+        assert_eq!(history.nodes[1].step.ops, vec![UndoOp::Insert { from: (0, 0), text: s!["Hi "] }]);
+    }
+
+    #[test]
+    fn backspaces_and_deletes_stored_as_single_op() {
+        let mut history = history(vec![delete(4, "o"), delete(3, "l")]);
+        assert_eq!(history.nodes[1].step.ops, vec![UndoOp::Delete { from: (0, 3), text: s!["lo"] }]);
+
+        history.begin_group();
+        push(&mut history, delete(0, "H"));
+        push(&mut history, delete(0, "e"));
+        history.end_group();
+        assert_eq!(history.nodes[2].step.ops, vec![UndoOp::Delete { from: (0, 0), text: s!["He"] }]);
+    }
+
+    #[test]
+    fn merged_op_undoes_as_one() {
+        let mut history = history(vec![insert(0, "H"), insert(1, "i")]);
+        assert_eq!(undo(&mut history), vec![(vec![delete(0, "Hi")], ((0, 0), None))]);
+    }
+
+    #[test]
+    fn budget_drops_oldest_steps() {
+        let mut history = history(vec![insert(0, "Hello"), insert(5, " World")]);
+        assert!(!history.is_truncated());
+
+        history.set_budget(history.nodes[2].step.size());
+        assert!(history.is_truncated());
+        assert_eq!(history.nodes.len(), 2);
+        assert_eq!(history.current(), 1);
+        assert_eq!(history.nodes[1].step.ops, vec![UndoOp::Insert { from: (0, 5), text: s![" World"] }]);
+
+        undo(&mut history);
+        assert_eq!(history.undo_target(), None);
+    }
+
+    #[test]
+    fn budget_keeps_newest_step() {
+        let mut history = history(vec![insert(0, "Hello"), insert(5, " World")]);
+        history.set_budget(1);
+        assert_eq!(history.nodes.len(), 2);
+        assert_eq!(history.current(), 1);
+        assert_eq!(undo(&mut history), vec![(vec![delete(5, " World")], ((0, 0), None))]);
+    }
+
+    #[test]
+    fn budget_keeps_branches_below_new_root() {
+        let mut history = history(vec![insert(0, "Hello"), insert(5, "!")]);
+        undo(&mut history);
+        push(&mut history, insert(5, "?"));
+        history.travel(2);
+
+        history.set_budget(history.size - history.nodes[1].step.size());
+        assert_eq!(history.nodes.len(), 3);
+        assert_eq!(history.nodes[0].children, vec![1, 2]);
+        assert_eq!(history.current(), 1);
+    }
+
+    #[test]
+    fn undo_redo_travel() {
+        let mut history = history(vec![insert(0, "H"), insert(1, "ello")]);
+        assert_eq!(undo(&mut history), vec![(vec![delete(1, "ello")], ((0, 0), None))]);
+        assert_eq!(redo(&mut history), vec![(vec![insert(1, "ello")], ((0, 5), None))]);
+        assert_eq!(history.redo_target(), None);
+    }
+
+    #[test]
+    fn edit_after_undo_keeps_branch() {
+        let mut history = history(vec![insert(0, "H"), insert(1, "ello")]);
+        undo(&mut history);
+        push(&mut history, insert(1, "i!"));
+
+        assert_eq!(history.nodes.len(), 4);
+        assert_eq!(history.earlier_target(), Some(2));
+        assert_eq!(
+            history.travel(2),
+            vec![(vec![delete(1, "i!")], ((0, 0), None)), (vec![insert(1, "ello")], ((0, 5), None))]
+        );
+    }
+
+    #[test]
+    fn redo_follows_last_visited_branch() {
+        let mut history = history(vec![insert(0, "H"), insert(1, "ello")]);
+        undo(&mut history);
+        push(&mut history, insert(1, "i!"));
+        history.travel(2);
+        undo(&mut history);
+        assert_eq!(history.redo_target(), Some(2));
+    }
+
+    #[test]
+    fn restore_rebuilds_branches() {
+        let step = |col| UndoStep { ops: vec![(&insert(col, "x")).into()], before: ((0, col), None), after: ((0, col + 1), None) };
+        let nodes = vec![UndoNode::new(step(0), 0), UndoNode::new(step(0), 0), UndoNode::new(step(1), 1), UndoNode::new(step(1), 1)];
+
+        let history = UndoHistory::restore(nodes, 2).unwrap();
+        assert_eq!(history.nodes[1].children, vec![2, 3]);
+        assert_eq!((history.undo_target(), history.redo_target()), (Some(1), None));
+    }
+
+    #[test]
+    fn restore_rejects_invalid_parents() {
+        let step = || UndoStep { ops: Vec::new(), before: ((0, 0), None), after: ((0, 0), None) };
+        assert!(UndoHistory::restore(vec![UndoNode::new(step(), 0), UndoNode::new(step(), 1)], 0).is_none());
+    }
+
+    #[test]
+    fn typing_after_undo_starts_branch() {
+        let mut history = history(vec![insert(0, "H")]);
+        push(&mut history, insert(1, "ello"));
+        undo(&mut history);
+        push(&mut history, insert(1, "i"));
+        assert_eq!(steps(&history), vec![1, 1, 1]);
+    }
+}
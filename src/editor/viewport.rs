@@ -56,6 +56,11 @@ impl Viewport {
         self.scroll_cmd((scroll_into(row, self.top, self.height), scroll_into(col, self.left, self.width)))
     }
 
+    pub fn center_on(&self, pos @ (row, _): PosInDocument) -> ScrollCommand {
+        let left = self.scroll_into_view(pos).map_or(self.left, |(_, left)| left);
+        self.scroll_cmd((row.saturating_sub(self.height as usize / 2), left))
+    }
+
     pub fn scroll_up(&self, n: usize) -> ScrollCommand {
         let new_top = self.top - min(n, self.top);
         self.scroll_cmd((new_top, self.left))
@@ -0,0 +1,276 @@
+use std::{env, fs, path::PathBuf};
+
+use crate::editor::settings::{Color, Colors, Settings, STATUS_FIELDS};
+use super::save::Backup;
+
+
+const FILE_NAME: &str = "config.toml";
+const PROJECT_FILE_NAME: &str = ".picow.toml";
+const MAX_TAB_WIDTH: i64 = 16;
+
+#[derive(PartialEq, Debug)]
+enum Value {
+    String(String),
+    Integer(i64),
+    Boolean(bool),
+}
+
+pub fn load(file_name: &str) -> (Settings, Vec<String>) {
+    let mut settings = Settings::default();
+    let mut warnings = Vec::new();
+
+    for path in user_config().into_iter().chain(project_config(file_name)) {
+        match fs::read_to_string(&path) {
+            Ok(text) => warnings.extend(apply(&mut settings, &text, &path.to_string_lossy())),
+            Err(err) => warnings.push(format!("{}: {}", path.to_string_lossy(), err)),
+        }
+    }
+    (settings, warnings)
+}
+
+fn user_config() -> Option<PathBuf> {
+    let config_dir = env::var_os("APPDATA")
+        .or_else(|| env::var_os("XDG_CONFIG_HOME"))
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+
+    Some(config_dir.join("picow").join(FILE_NAME)).filter(|path| path.is_file())
+}
+
+fn project_config(file_name: &str) -> Option<PathBuf> {
+    let path = fs::canonicalize(file_name).ok()?;
+    path.ancestors().skip(1).map(|dir| dir.join(PROJECT_FILE_NAME)).find(|path| path.is_file())
+}
+
+
+fn apply(settings: &mut Settings, text: &str, source: &str) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let mut table = String::new();
+
+    for (idx, line) in text.lines().enumerate() {
+        let line = strip_comment(line).trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let result = match line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            Some(name) => { table = name.trim().to_string(); Ok(()) },
+            None => parse_entry(line).and_then(|(key, value)| set(settings, &table, key, value)),
+        };
+        if let Err(message) = result {
+            warnings.push(format!("{}:{}: {}", source, idx + 1, message));
+        }
+    }
+    warnings
+}
+
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+
+    for (idx, c) in line.char_indices() {
+        match (quote, c) {
+            (Some('"'), '\\') if !escaped => { escaped = true; continue },
+            (Some(q), c) if c == q && !escaped => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..idx],
+            _ => {}
+        }
+        escaped = false;
+    }
+    line
+}
+
+fn parse_entry(line: &str) -> Result<(&str, Value), String> {
+    let (key, value) = line.split_once('=').ok_or_else(|| format!("expected key = value, found '{}'", line))?;
+    let key = key.trim();
+    if key.is_empty() {
+        return Err(String::from("missing key"));
+    }
+    Ok((key, parse_value(value.trim())?))
+}
+
+fn parse_value(value: &str) -> Result<Value, String> {
+    if let Some(text) = value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+        return unescape(text).map(Value::String);
+    }
+    if let Some(text) = value.strip_prefix('\'').and_then(|value| value.strip_suffix('\'')) {
+        return Ok(Value::String(text.to_string()));
+    }
+
+    match value {
+        "true" => Ok(Value::Boolean(true)),
+        "false" => Ok(Value::Boolean(false)),
+        _ => value.replace('_', "").parse().map(Value::Integer).map_err(|_| format!("invalid value '{}'", value)),
+    }
+}
+
+fn unescape(text: &str) -> Result<String, String> {
+    let mut result = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            result.push(c);
+            continue;
+        }
+        result.push(match chars.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('"') => '"',
+            Some('\\') => '\\',
+            other => return Err(format!("invalid escape '\\{}'", other.map(String::from).unwrap_or_default())),
+        });
+    }
+    Ok(result)
+}
+
+
+fn set(settings: &mut Settings, table: &str, key: &str, value: Value) -> Result<(), String> {
+    let name = if table.is_empty() { key.to_string() } else { format!("{}.{}", table, key) };
+    let expected = |kind: &str| Err(format!("'{}' must be {}", name, kind));
+
+    match (name.as_str(), value) {
+        ("editor.tab_width", Value::Integer(n)) if (1..=MAX_TAB_WIDTH).contains(&n) => settings.tab_width = n as usize,
+        ("editor.tab_width", _) => return expected(&format!("a number from 1 to {}", MAX_TAB_WIDTH)),
+        ("editor.use_tabs", Value::Boolean(b)) => settings.use_tabs = b,
+        ("editor.auto_indent", Value::Boolean(b)) => settings.auto_indent = b,
+        ("editor.auto_close_pairs", Value::Boolean(b)) => settings.auto_close_pairs = b,
+        ("editor.persist_undo", Value::Boolean(b)) => settings.persist_undo = b,
+        ("editor.use_tabs" | "editor.auto_indent" | "editor.auto_close_pairs" | "editor.persist_undo", _) => return expected("true or false"),
+        ("editor.undo_limit_mb", Value::Integer(n)) if n > 0 => settings.undo_budget = usize::try_from(n).ok()
+            .and_then(|n| n.checked_mul(1024 * 1024))
+            .ok_or_else(|| format!("'{}' is too large", name))?,
+        ("editor.undo_limit_mb", _) => return expected("a positive number"),
+        ("editor.backup", Value::String(backup)) if !backup.is_empty() => settings.backup = match backup.as_str() {
+            "none" => Backup::None,
+            "same_dir" => Backup::SameDir,
+            dir => Backup::Dir(PathBuf::from(dir)),
+        },
+        ("editor.backup", _) => return expected("\"none\", \"same_dir\" or a directory"),
+        ("status.format", Value::String(format)) => settings.status_format = status_format(format)?,
+        ("status.format", _) => return expected("a string"),
+        (name, value) => match name.strip_prefix("colors.").and_then(|key| color(&mut settings.colors, key)) {
+            Some(color) => *color = match value {
+                Value::String(ref name) => Color::parse(name).ok_or_else(|| format!("unknown color '{}'", name))?,
+                _ => return expected("a color name"),
+            },
+            None => return Err(format!("unknown setting '{}'", name)),
+        },
+    }
+    Ok(())
+}
+
+fn status_format(format: String) -> Result<String, String> {
+    let mut rest = format.as_str();
+
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or("unclosed '{' in status.format")? + start;
+        let field = &rest[start + 1..end];
+        if !STATUS_FIELDS.contains(&field) {
+            return Err(format!("unknown status field '{{{}}}', expected one of {}", field, STATUS_FIELDS.join(", ")));
+        }
+        rest = &rest[end + 1..];
+    }
+    Ok(format)
+}
+
+fn color<'a>(colors: &'a mut Colors, key: &str) -> Option<&'a mut Color> {
+    match key {
+        "comment" => Some(&mut colors.comment),
+        "keyword" => Some(&mut colors.keyword),
+        "type" => Some(&mut colors.types),
+        "string" => Some(&mut colors.string),
+        "number" => Some(&mut colors.number),
+        "key" => Some(&mut colors.key),
+        "heading" => Some(&mut colors.heading),
+        "variable" => Some(&mut colors.variable),
+        "selection" => Some(&mut colors.selection),
+        "match" => Some(&mut colors.search_match),
+        "bracket" => Some(&mut colors.bracket),
+        "caret" => Some(&mut colors.caret),
+        _ => None
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn settings(text: &str) -> (Settings, Vec<String>) {
+        let mut settings = Settings::default();
+        let warnings = apply(&mut settings, text, "config.toml");
+        (settings, warnings)
+    }
+
+    #[test]
+    fn apply_tables() {
+        let (settings, warnings) = settings(concat!(
+            "# picow settings\n",
+            "[editor]\n",
+            "tab_width = 2  # narrow\n",
+            "auto_close_pairs = false\n",
+            "\n",
+            "[status]\n",
+            "format = \"{position} # {mode}\"\n",
+            "[colors]\n",
+            "keyword = 'bright-blue'\n",
+        ));
+        assert_eq!(warnings, Vec::<String>::new());
+        assert_eq!(settings.tab_width, 2);
+        assert!(!settings.auto_close_pairs);
+        assert_eq!(settings.status_format, "{position} # {mode}");
+        assert_eq!(settings.colors.keyword, Color::parse("bright-blue").unwrap());
+    }
+
+    #[test]
+    fn warnings_for_invalid_entries() {
+        let (settings, warnings) = settings("[editor]\ntab_width = \"4\"\nfoo = 1\nuse_tabs\n[colors]\nstring = \"purple\"\n[editor]\nuse_tabs = true\n");
+        assert_eq!(warnings, vec![
+            "config.toml:2: 'editor.tab_width' must be a number from 1 to 16",
+            "config.toml:3: unknown setting 'editor.foo'",
+            "config.toml:4: expected key = value, found 'use_tabs'",
+            "config.toml:6: unknown color 'purple'",
+        ]);
+        assert_eq!((settings.tab_width, settings.use_tabs), (4, true));
+    }
+
+    #[test]
+    fn undo_limit_overflow() {
+        let (settings, warnings) = settings("[editor]\nundo_limit_mb = 9_000_000_000_000_000\n");
+        assert_eq!(warnings, vec!["config.toml:2: 'editor.undo_limit_mb' is too large"]);
+        assert_eq!(settings.undo_budget, Settings::default().undo_budget);
+    }
+
+    #[test]
+    fn persist_undo() {
+        assert!(settings("").0.persist_undo);
+        assert!(!settings("[editor]\npersist_undo = false\n").0.persist_undo);
+        assert_eq!(settings("[editor]\npersist_undo = 0\n").1, vec!["config.toml:2: 'editor.persist_undo' must be true or false"]);
+    }
+
+    #[test]
+    fn backup() {
+        assert_eq!(settings("[editor]\nbackup = \"same_dir\"\n").0.backup, Backup::SameDir);
+        assert_eq!(settings("[editor]\nbackup = '/tmp/backup'\n").0.backup, Backup::Dir(PathBuf::from("/tmp/backup")));
+        assert_eq!(settings("[editor]\nbackup = \"same_dir\"\nbackup = \"none\"\n").0.backup, Backup::None);
+        assert_eq!(settings("[editor]\nbackup = true\n").1, vec!["config.toml:2: 'editor.backup' must be \"none\", \"same_dir\" or a directory"]);
+    }
+
+    #[test]
+    fn unknown_status_field() {
+        let (settings, warnings) = settings("[status]\nformat = \"{line}\"\n");
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("unknown status field '{line}'"));
+        assert_eq!(settings.status_format, Settings::default().status_format);
+    }
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(parse_value("1_000"), Ok(Value::Integer(1000)));
+        assert_eq!(parse_value(r#""a\tb\"""#), Ok(Value::String(String::from("a\tb\""))));
+        assert_eq!(parse_value(r"'C:\temp'"), Ok(Value::String(String::from(r"C:\temp"))));
+        assert!(parse_value("yes").is_err());
+    }
+}
@@ -0,0 +1,116 @@
+use std::{cmp::max, fmt};
+
+use self::DiffLine::*;
+
+const MAX_TABLE_SIZE: usize = 4_000_000;
+
+
+#[derive(PartialEq, Debug)]
+pub enum DiffLine<'a> {
+    Same(&'a str),
+    Deleted(&'a str),
+    Inserted(&'a str),
+}
+
+impl fmt::Display for DiffLine<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Same(line) => write!(f, "  {}", line),
+            Deleted(line) => write!(f, "- {}", line),
+            Inserted(line) => write!(f, "+ {}", line),
+        }
+    }
+}
+
+
+pub fn diff<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    let prefix = common_len(old.iter(), new.iter());
+    let suffix = common_len(old[prefix..].iter().rev(), new[prefix..].iter().rev());
+
+    let old_mid = &old[prefix..old.len() - suffix];
+    let new_mid = &new[prefix..new.len() - suffix];
+
+    let mut lines: Vec<DiffLine> = old[..prefix].iter().map(|line| Same(line)).collect();
+    lines.extend(diff_middle(old_mid, new_mid));
+    lines.extend(old[old.len() - suffix..].iter().map(|line| Same(line)));
+    lines
+}
+
+fn common_len<'a, I>(old: I, new: I) -> usize
+where
+    I: Iterator<Item = &'a &'a str>,
+{
+    old.zip(new).take_while(|(o, n)| o == n).count()
+}
+
+fn diff_middle<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<DiffLine<'a>> {
+    if old.len() * new.len() > MAX_TABLE_SIZE {
+        return old.iter().map(|line| Deleted(line))
+            .chain(new.iter().map(|line| Inserted(line)))
+            .collect();
+    }
+
+    let lcs = lcs_table(old, new);
+    let mut lines = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            lines.push(Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            lines.push(Deleted(old[i]));
+            i += 1;
+        } else {
+            lines.push(Inserted(new[j]));
+            j += 1;
+        }
+    }
+
+    lines
+}
+
+fn lcs_table(old: &[&str], new: &[&str]) -> Vec<Vec<usize>> {
+    let mut lcs = vec![vec![0; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lcs[i][j] = if old[i] == new[j] { lcs[i + 1][j + 1] + 1 } else { max(lcs[i + 1][j], lcs[i][j + 1]) };
+        }
+    }
+
+    lcs
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn no_change() {
+        assert_eq!(diff(&["a", "b"], &["a", "b"]), vec![Same("a"), Same("b")]);
+    }
+
+    #[test]
+    fn changed_line() {
+        assert_eq!(
+            diff(&["a", "b", "c"], &["a", "x", "c"]),
+            vec![Same("a"), Deleted("b"), Inserted("x"), Same("c")]
+        );
+    }
+
+    #[test]
+    fn inserted_and_deleted_lines() {
+        assert_eq!(
+            diff(&["a", "b", "c", "d"], &["b", "c", "x", "d", "e"]),
+            vec![Deleted("a"), Same("b"), Same("c"), Inserted("x"), Same("d"), Inserted("e")]
+        );
+    }
+
+    #[test]
+    fn to_string() {
+        assert_eq!(Inserted("Hello").to_string(), "+ Hello");
+    }
+}
@@ -0,0 +1,280 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use regex::Regex;
+
+use crate::editor::{content::{CR, CRLF, LF}, indent::Indent};
+use super::encoding::{self, Charset};
+
+
+const FILE_NAME: &str = ".editorconfig";
+const MAX_RANGE: i64 = 1000;
+
+#[derive(Default, PartialEq, Debug)]
+pub struct EditorConfig {
+    pub indent: Option<Indent>,
+    pub end_of_line: Option<&'static str>,
+    pub charset: Option<Charset>,
+    pub trim_trailing_whitespace: bool,
+    pub insert_final_newline: bool,
+}
+
+type Section = (String, Vec<(String, String)>);
+
+pub fn load(file_name: &str) -> io::Result<EditorConfig> {
+    let path = fs::canonicalize(file_name)?;
+    let mut configs = Vec::new();
+
+    for dir in path.ancestors().skip(1) {
+        let Ok(text) = fs::read_to_string(dir.join(FILE_NAME)) else { continue };
+        let (is_root, sections) = parse(&text);
+        configs.push((dir, sections));
+        if is_root { break }
+    }
+
+    let mut properties = HashMap::new();
+    for (dir, sections) in configs.iter().rev() {
+        apply(sections, &relative_path(&path, dir), &mut properties);
+    }
+    Ok(EditorConfig::from(&properties))
+}
+
+fn relative_path(path: &Path, dir: &Path) -> String {
+    let relative = path.strip_prefix(dir).unwrap_or(path);
+    relative.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+fn parse(text: &str) -> (bool, Vec<Section>) {
+    let mut is_root = false;
+    let mut sections: Vec<Section> = Vec::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with(['#', ';']) {
+            continue;
+        }
+
+        if let Some(glob) = line.strip_prefix('[').and_then(|rest| rest.rfind(']').map(|end| &rest[..end])) {
+            sections.push((glob.to_string(), Vec::new()));
+        } else if let Some((key, value)) = line.split_once('=') {
+            let (key, value) = (key.trim().to_lowercase(), value.trim().to_lowercase());
+            match sections.last_mut() {
+                Some((_, properties)) => properties.push((key, value)),
+                None => is_root |= key == "root" && value == "true",
+            }
+        }
+    }
+    (is_root, sections)
+}
+
+fn apply(sections: &[Section], path: &str, properties: &mut HashMap<String, String>) {
+    for (glob, section) in sections {
+        if !matches(glob, path) {
+            continue;
+        }
+        for (key, value) in section {
+            match value.as_str() {
+                "unset" => properties.remove(key),
+                _ => properties.insert(key.clone(), value.clone()),
+            };
+        }
+    }
+}
+
+fn matches(glob: &str, path: &str) -> bool {
+    let glob = match glob.contains('/') {
+        true => glob.strip_prefix('/').unwrap_or(glob).to_string(),
+        false => format!("**/{}", glob),
+    };
+
+    Regex::new(&format!("^{}$", glob_to_regex(&glob)))
+        .map(|regex| regex.is_match(path))
+        .unwrap_or(false)
+}
+
+fn glob_to_regex(glob: &str) -> String {
+    let chars: Vec<char> = glob.chars().collect();
+    let mut regex = String::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        match chars[i] {
+            '*' if chars.get(i + 1) == Some(&'*') && chars.get(i + 2) == Some(&'/') => { regex.push_str("(?:.*/)?"); i += 2 },
+            '*' if chars.get(i + 1) == Some(&'*') => { regex.push_str(".*"); i += 1 },
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '\\' if i + 1 < chars.len() => { regex.push_str(&regex::escape(&chars[i + 1].to_string())); i += 1 },
+            '[' => match chars[i..].iter().position(|&c| c == ']') {
+                Some(len) => {
+                    let class: String = chars[i + 1..i + len].iter().collect();
+                    let class = class.strip_prefix('!').map(|class| format!("^{}", class)).unwrap_or(class);
+                    regex.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+                    i += len;
+                },
+                None => regex.push_str("\\["),
+            },
+            '{' => match closing_brace(&chars[i..]) {
+                Some(len) => {
+                    regex.push_str(&braces(&chars[i + 1..i + len].iter().collect::<String>()));
+                    i += len;
+                },
+                None => regex.push_str("\\{"),
+            },
+            c => regex.push_str(&regex::escape(&c.to_string())),
+        }
+        i += 1;
+    }
+    regex
+}
+
+fn closing_brace(chars: &[char]) -> Option<usize> {
+    let mut depth = 0;
+    for (i, &c) in chars.iter().enumerate() {
+        match c {
+            '{' => depth += 1,
+            '}' => { depth -= 1; if depth == 0 { return Some(i) } },
+            _ => {}
+        }
+    }
+    None
+}
+
+fn braces(inner: &str) -> String {
+    if let Some((from, to)) = inner.split_once("..") {
+        if let (Ok(from), Ok(to)) = (from.parse::<i64>(), to.parse::<i64>()) {
+            let (from, to) = (from.min(to), from.max(to));
+            if to - from <= MAX_RANGE {
+                return format!("(?:{})", (from..=to).map(|n| n.to_string()).collect::<Vec<_>>().join("|"));
+            }
+        }
+    }
+
+    let alternatives = split_top_level(inner);
+    match alternatives.len() {
+        1 => format!("\\{{{}\\}}", glob_to_regex(inner)),
+        _ => format!("(?:{})", alternatives.iter().map(|alternative| glob_to_regex(alternative)).collect::<Vec<_>>().join("|")),
+    }
+}
+
+fn split_top_level(inner: &str) -> Vec<String> {
+    let mut parts = vec![String::new()];
+    let mut depth = 0;
+
+    for c in inner.chars() {
+        match c {
+            ',' if depth == 0 => { parts.push(String::new()); continue },
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            _ => {}
+        }
+        parts.last_mut().unwrap().push(c);
+    }
+    parts
+}
+
+impl From<&HashMap<String, String>> for EditorConfig {
+    fn from(properties: &HashMap<String, String>) -> Self {
+        let get = |key: &str| properties.get(key).map(String::as_str);
+        let number = |key: &str| get(key).and_then(|value| value.parse::<usize>().ok()).filter(|&n| n > 0);
+
+        let size = match get("indent_size") {
+            Some("tab") => number("tab_width"),
+            _ => number("indent_size").or(number("tab_width")),
+        };
+        let indent = match get("indent_style") {
+            Some("tab") => Some(Indent::Tabs),
+            Some("space") => Some(Indent::Spaces(size.unwrap_or(4))),
+            _ => None,
+        };
+
+        Self {
+            indent,
+            end_of_line: match get("end_of_line") {
+                Some("lf") => Some(LF),
+                Some("crlf") => Some(CRLF),
+                Some("cr") => Some(CR),
+                _ => None
+            },
+            charset: get("charset").and_then(encoding::charset),
+            trim_trailing_whitespace: get("trim_trailing_whitespace") == Some("true"),
+            insert_final_newline: get("insert_final_newline") == Some("true"),
+        }
+    }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{env, process};
+
+    use super::*;
+
+    fn test_dir(name: &str) -> std::path::PathBuf {
+        let dir = env::temp_dir().join(format!("picow-editorconfig-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn glob_matching() {
+        assert!(matches("*", "src/main.rs"));
+        assert!(matches("*.{rs,toml}", "Cargo.toml"));
+        assert!(!matches("*.{rs,toml}", "README.md"));
+        assert!(matches("src/*.rs", "src/main.rs"));
+        assert!(!matches("src/*.rs", "src/editor/state.rs"));
+        assert!(matches("src/**.rs", "src/editor/state.rs"));
+        assert!(matches("/lib/**/*.js", "lib/a/b/c.js"));
+        assert!(matches("file[0-9].txt", "dir/file7.txt"));
+        assert!(!matches("file[!0-9].txt", "file7.txt"));
+        assert!(matches("log{1..12}.txt", "log10.txt"));
+        assert!(!matches("log{1..12}.txt", "log13.txt"));
+    }
+
+    #[test]
+    fn parse_sections() {
+        let (is_root, sections) = parse("root = true\n; comment\n[*.md]\nTrim_Trailing_Whitespace = False\n");
+        assert!(is_root);
+        assert_eq!(sections, vec![(s("*.md"), vec![(s("trim_trailing_whitespace"), s("false"))])]);
+    }
+
+    #[test]
+    fn later_sections_override_and_unset() {
+        let (_, sections) = parse("[*]\nindent_style = tab\nend_of_line = crlf\n[*.rs]\nindent_style = space\nindent_size = 2\nend_of_line = unset\n");
+        let mut properties = HashMap::new();
+        apply(&sections, "src/main.rs", &mut properties);
+
+        let config = EditorConfig::from(&properties);
+        assert_eq!(config.indent, Some(Indent::Spaces(2)));
+        assert_eq!(config.end_of_line, None);
+    }
+
+    #[test]
+    fn indent_size_without_style_keeps_indent() {
+        let properties = HashMap::from([(s("indent_size"), s("2"))]);
+        assert_eq!(EditorConfig::from(&properties).indent, None);
+    }
+
+    #[test]
+    fn indent_size_tab_uses_tab_width() {
+        let properties = HashMap::from([(s("indent_style"), s("space")), (s("indent_size"), s("tab")), (s("tab_width"), s("8"))]);
+        assert_eq!(EditorConfig::from(&properties).indent, Some(Indent::Spaces(8)));
+    }
+
+    #[test]
+    fn load_walks_up_to_root() {
+        let dir = test_dir("walk");
+        let sub = dir.join("sub");
+        fs::create_dir_all(&sub).unwrap();
+        fs::write(dir.join(FILE_NAME), "root = true\n[*]\ncharset = latin1\ninsert_final_newline = true\n").unwrap();
+        fs::write(sub.join(FILE_NAME), "[*.ps1]\ncharset = utf-8-bom\n").unwrap();
+        fs::write(sub.join("script.ps1"), "").unwrap();
+
+        let config = load(sub.join("script.ps1").to_str().unwrap()).unwrap();
+        assert_eq!(config.charset, Some(Charset::Utf8Bom));
+        assert!(config.insert_final_newline);
+        assert!(!config.trim_trailing_whitespace);
+    }
+
+    fn s(text: &str) -> String {
+        text.to_string()
+    }
+}
@@ -0,0 +1,101 @@
+use std::io::{self, ErrorKind};
+
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Charset {
+    Utf8,
+    Utf8Bom,
+    Latin1,
+    Utf16Le,
+    Utf16Be,
+}
+
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+pub fn charset(name: &str) -> Option<Charset> {
+    match name {
+        "utf-8" => Some(Charset::Utf8),
+        "utf-8-bom" => Some(Charset::Utf8Bom),
+        "latin1" => Some(Charset::Latin1),
+        "utf-16le" => Some(Charset::Utf16Le),
+        "utf-16be" => Some(Charset::Utf16Be),
+        _ => None
+    }
+}
+
+pub fn decode(bytes: &[u8], charset: Option<Charset>) -> io::Result<String> {
+    match charset {
+        None | Some(Charset::Utf8) => utf8(bytes.to_vec()),
+        Some(Charset::Utf8Bom) => utf8(bytes.strip_prefix(UTF8_BOM).unwrap_or(bytes).to_vec()),
+        Some(Charset::Latin1) => Ok(bytes.iter().map(|&b| b as char).collect()),
+        Some(Charset::Utf16Le) => utf16(bytes, u16::from_le_bytes),
+        Some(Charset::Utf16Be) => utf16(bytes, u16::from_be_bytes),
+    }
+}
+
+pub fn encode(text: &str, charset: Option<Charset>) -> io::Result<Vec<u8>> {
+    match charset {
+        None | Some(Charset::Utf8) => Ok(text.as_bytes().to_vec()),
+        Some(Charset::Utf8Bom) => Ok([UTF8_BOM, text.as_bytes()].concat()),
+        Some(Charset::Latin1) => text.chars()
+            .map(|c| u8::try_from(c).map_err(|_| invalid(&format!("'{}' cannot be saved as latin1", c))))
+            .collect(),
+        Some(Charset::Utf16Le) => Ok(utf16_bytes(text, u16::to_le_bytes)),
+        Some(Charset::Utf16Be) => Ok(utf16_bytes(text, u16::to_be_bytes)),
+    }
+}
+
+fn utf8(bytes: Vec<u8>) -> io::Result<String> {
+    String::from_utf8(bytes).map_err(|_| invalid("stream did not contain valid UTF-8"))
+}
+
+fn utf16(bytes: &[u8], unit: fn([u8; 2]) -> u16) -> io::Result<String> {
+    if !bytes.len().is_multiple_of(2) {
+        return Err(invalid("odd number of bytes in UTF-16 file"));
+    }
+
+    let units: Vec<u16> = bytes.chunks(2).map(|pair| unit([pair[0], pair[1]])).collect();
+    let units = units.strip_prefix(&[0xFEFF]).unwrap_or(&units);
+    String::from_utf16(units).map_err(|_| invalid("stream did not contain valid UTF-16"))
+}
+
+fn utf16_bytes(text: &str, bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+    "\u{FEFF}".encode_utf16().chain(text.encode_utf16()).flat_map(bytes).collect()
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, message)
+}
+
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn utf8_bom_round_trip() {
+        let bytes = encode("Hi", Some(Charset::Utf8Bom)).unwrap();
+        assert_eq!(bytes, b"\xEF\xBB\xBFHi");
+        assert_eq!(decode(&bytes, Some(Charset::Utf8Bom)).unwrap(), "Hi");
+    }
+
+    #[test]
+    fn latin1_round_trip() {
+        assert_eq!(decode(b"caf\xE9", Some(Charset::Latin1)).unwrap(), "café");
+        assert_eq!(encode("café", Some(Charset::Latin1)).unwrap(), b"caf\xE9");
+        assert!(encode("€", Some(Charset::Latin1)).is_err());
+    }
+
+    #[test]
+    fn utf16_round_trip() {
+        let bytes = encode("a€", Some(Charset::Utf16Le)).unwrap();
+        assert_eq!(bytes, b"\xFF\xFEa\x00\xAC\x20");
+        assert_eq!(decode(&bytes, Some(Charset::Utf16Le)).unwrap(), "a€");
+        assert_eq!(decode(b"\x00a", Some(Charset::Utf16Be)).unwrap(), "a");
+    }
+
+    #[test]
+    fn invalid_utf8() {
+        assert_eq!(decode(b"\xFF", None).unwrap_err().kind(), ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,422 @@
+use std::{cmp::Reverse, env, fs, io::{self, ErrorKind}, iter, path::{Path, PathBuf}, str::Lines, time::{Duration, SystemTime, UNIX_EPOCH}};
+
+use crate::editor::{content::EditorContent, edit::{self, EditOp}, pos::PosInDocument, undo::{CursorState, UndoHistory, UndoNode, UndoOp::{self, *}, UndoStep}};
+
+const HEADER: &str = "picow-undo 1";
+const EXTENSION: &str = "undo";
+const MAX_FILE_SIZE: usize = 4 * 1024 * 1024;
+const MAX_DIR_SIZE: u64 = 64 * 1024 * 1024;
+const MAX_AGE: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+
+pub struct HistoryStore {
+    dir: PathBuf,
+}
+
+impl HistoryStore {
+    pub fn new() -> Self {
+        let data_dir = env::var_os("LOCALAPPDATA")
+            .or_else(|| env::var_os("HOME"))
+            .map(PathBuf::from)
+            .unwrap_or_else(env::temp_dir);
+
+        Self::in_dir(data_dir.join("picow").join("undo"))
+    }
+
+    pub fn in_dir(dir: PathBuf) -> Self {
+        Self { dir }
+    }
+
+    pub fn save(&self, file_name: &str, content: &str, history: &UndoHistory) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        fs::write(self.path(file_name), write(content, history))?;
+        self.prune(MAX_AGE, MAX_DIR_SIZE)
+    }
+
+    pub fn remove(&self, file_name: &str) -> io::Result<()> {
+        match fs::remove_file(self.path(file_name)) {
+            Err(err) if err.kind() != ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn load(&self, file_name: &str, content: &str) -> io::Result<Option<UndoHistory>> {
+        match fs::read_to_string(self.path(file_name)) {
+            Ok(text) => Ok(read(&text, content)),
+            Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn path(&self, file_name: &str) -> PathBuf {
+        let path = fs::canonicalize(file_name).unwrap_or_else(|_| Path::new(file_name).to_path_buf());
+        self.dir.join(format!("{:016x}.{}", hash(path.to_string_lossy().as_bytes()), EXTENSION))
+    }
+
+    fn prune(&self, max_age: Duration, max_size: u64) -> io::Result<()> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|extension| extension == EXTENSION) {
+                let metadata = fs::metadata(&path)?;
+                files.push((metadata.modified()?, metadata.len(), path));
+            }
+        }
+        files.sort_by_key(|&(modified, ..)| Reverse(modified));
+
+        let now = SystemTime::now();
+        let mut size = 0;
+        for (modified, len, path) in files {
+            size += len;
+            let is_old = now.duration_since(modified).is_ok_and(|age| age > max_age);
+            if is_old || size > max_size {
+                fs::remove_file(path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Default for HistoryStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn hash(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, &byte| (hash ^ byte as u64).wrapping_mul(0x100000001b3))
+}
+
+
+fn write(content: &str, history: &UndoHistory) -> String {
+    let (nodes, current) = persisted_nodes(history);
+    let mut text = vec![
+        HEADER.to_string(),
+        format!("{:016x}", hash(content.as_bytes())),
+        format!("{} {}", nodes.len(), current),
+    ];
+
+    for (parent, node) in nodes {
+        let UndoStep { ref ops, before, after } = node.step;
+        let at = node.at.duration_since(UNIX_EPOCH).map(|at| at.as_secs()).unwrap_or(0);
+        text.push(format!("{} {} {} {} {}", parent, at, cursor_state(before), cursor_state(after), ops.len()));
+
+        for op in ops {
+            let (header, lines): (String, Vec<&str>) = match op {
+                Insert { from, text } => (format!("I {} {}", pos(*from), line_count(text)), text.split('\n').collect()),
+                Delete { from, text } => (format!("D {} {}", pos(*from), line_count(text)), text.split('\n').collect()),
+                Replace { from, inserted, deleted } => (
+                    format!("R {} {} {}", pos(*from), line_count(inserted), line_count(deleted)),
+                    inserted.split('\n').chain(deleted.split('\n')).collect()
+                ),
+            };
+            text.push(header);
+            text.extend(lines.into_iter().map(escape));
+        }
+    }
+
+    text.join("\n") + "\n"
+}
+
+fn persisted_nodes(history: &UndoHistory) -> (Vec<(usize, &UndoNode)>, usize) {
+    let nodes = history.nodes();
+    if nodes.iter().map(|node| node.step.size()).sum::<usize>() <= MAX_FILE_SIZE {
+        return (nodes.iter().map(|node| (node.parent(), node)).collect(), history.current());
+    }
+
+    let mut path = Vec::new();
+    let mut size = 0;
+    let mut node = history.current();
+    while node != 0 && size + nodes[node].step.size() <= MAX_FILE_SIZE {
+        size += nodes[node].step.size();
+        path.push(node);
+        node = nodes[node].parent();
+    }
+    path.reverse();
+
+    let kept = iter::once((0, &nodes[0])).chain(path.iter().enumerate().map(|(idx, &node)| (idx, &nodes[node]))).collect();
+    (kept, path.len())
+}
+
+fn line_count(text: &str) -> usize {
+    text.matches('\n').count() + 1
+}
+
+fn pos((row, col): PosInDocument) -> String {
+    format!("{}:{}", row, col)
+}
+
+fn cursor_state((cursor, selection): CursorState) -> String {
+    format!("{} {}", pos(cursor), selection.map(pos).unwrap_or_else(|| "-".to_string()))
+}
+
+fn escape(line: &str) -> String {
+    line.replace('\\', "\\\\").replace('\r', "\\r").replace('\n', "\\n")
+}
+
+
+fn read(text: &str, content: &str) -> Option<UndoHistory> {
+    let mut lines = text.lines();
+
+    if lines.next()? != HEADER || lines.next()? != format!("{:016x}", hash(content.as_bytes())) {
+        return None;
+    }
+
+    let (count, current) = read_counts(lines.next()?)?;
+    let nodes = (0..count).map(|_| read_node(&mut lines)).collect::<Option<Vec<_>>>()?;
+
+    UndoHistory::restore(nodes, current).filter(|history| fits(history, content))
+}
+
+fn fits(history: &UndoHistory, content: &str) -> bool {
+    let nodes = history.nodes();
+    let mut content = EditorContent::parse(content);
+    let mut children = vec![Vec::new(); nodes.len()];
+    nodes.iter().enumerate().skip(1).for_each(|(idx, node)| children[node.parent()].push(idx));
+
+    let mut node = history.current();
+    if !cursor_fits(&content, nodes[node].step.after) {
+        return false;
+    }
+    while node != 0 {
+        if !undo_step(&mut content, &nodes[node].step) {
+            return false;
+        }
+        node = nodes[node].parent();
+    }
+
+    let mut stack: Vec<(usize, bool)> = children[0].iter().map(|&child| (child, true)).collect();
+    while let Some((node, is_redo)) = stack.pop() {
+        let step = &nodes[node].step;
+        let is_applied = if is_redo {
+            stack.push((node, false));
+            stack.extend(children[node].iter().map(|&child| (child, true)));
+            apply(&mut content, step.ops.iter().map(UndoOp::edit_op)) && cursor_fits(&content, step.after)
+        } else {
+            undo_step(&mut content, step)
+        };
+        if !is_applied {
+            return false;
+        }
+    }
+    true
+}
+
+fn undo_step(content: &mut EditorContent, step: &UndoStep) -> bool {
+    apply(content, step.ops.iter().rev().map(|op| op.edit_op().inverse())) && cursor_fits(content, step.before)
+}
+
+fn apply(content: &mut EditorContent, mut edit_ops: impl Iterator<Item = EditOp>) -> bool {
+    edit_ops.all(|edit_op| {
+        let is_valid = op_fits(content, &edit_op);
+        if is_valid {
+            edit::process(content, &edit_op);
+        }
+        is_valid
+    })
+}
+
+fn op_fits(content: &EditorContent, edit_op: &EditOp) -> bool {
+    let (from, to) = (edit_op.from(), edit_op.deleted_to());
+    if !pos_fits(content, from) || !pos_fits(content, to) {
+        return false;
+    }
+
+    match edit_op {
+        EditOp::Insert { .. } => true,
+        EditOp::Delete { lines, .. } | EditOp::Replace { deleted_lines: lines, .. } => content.selection(from, to) == *lines,
+    }
+}
+
+fn cursor_fits(content: &EditorContent, (cursor, selection): CursorState) -> bool {
+    [Some(cursor), selection].into_iter().flatten().all(|pos| pos_fits(content, pos))
+}
+
+fn pos_fits(content: &EditorContent, (row, col): PosInDocument) -> bool {
+    row < content.lines.len() && col <= content.line_len(row)
+}
+
+fn read_node(lines: &mut Lines) -> Option<UndoNode> {
+    let fields: Vec<&str> = lines.next()?.split(' ').collect();
+    let [parent, at, cursor, selection, after_cursor, after_selection, op_count] = fields[..] else { return None };
+
+    let ops = (0..op_count.parse().ok()?).map(|_| read_op(lines)).collect::<Option<Vec<_>>>()?;
+    let before = (read_pos(cursor)?, read_selection(selection)?);
+    let after = (read_pos(after_cursor)?, read_selection(after_selection)?);
+
+    let mut node = UndoNode::new(UndoStep { ops, before, after }, parent.parse().ok()?);
+    node.at = UNIX_EPOCH + Duration::from_secs(at.parse().ok()?);
+    Some(node)
+}
+
+fn read_op(lines: &mut Lines) -> Option<UndoOp> {
+    let fields: Vec<&str> = lines.next()?.split(' ').collect();
+
+    match fields[..] {
+        ["I", from, count] => Some(Insert { from: read_pos(from)?, text: read_text(lines, count)? }),
+        ["D", from, count] => Some(Delete { from: read_pos(from)?, text: read_text(lines, count)? }),
+        ["R", from, inserted, deleted] => Some(Replace {
+            from: read_pos(from)?,
+            inserted: read_text(lines, inserted)?,
+            deleted: read_text(lines, deleted)?,
+        }),
+        _ => None
+    }
+}
+
+fn read_text(lines: &mut Lines, count: &str) -> Option<String> {
+    let lines = (0..count.parse().ok()?).map(|_| lines.next().map(unescape)).collect::<Option<Vec<_>>>()?;
+    Some(lines.join("\n"))
+}
+
+fn read_pos(pos: &str) -> Option<PosInDocument> {
+    let (row, col) = pos.split_once(':')?;
+    Some((row.parse().ok()?, col.parse().ok()?))
+}
+
+fn read_selection(selection: &str) -> Option<Option<PosInDocument>> {
+    if selection == "-" { Some(None) } else { read_pos(selection).map(Some) }
+}
+
+fn read_counts(line: &str) -> Option<(usize, usize)> {
+    let (a, b) = line.split_once(' ')?;
+    Some((a.parse().ok()?, b.parse().ok()?))
+}
+
+fn unescape(line: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = line.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('r') => unescaped.push('\r'),
+            Some('n') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => {}
+        }
+    }
+    unescaped
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::process;
+
+    use crate::{editor::{edit::EditOp, row::Row}, vecr};
+    use super::*;
+
+    const CONTENT: &str = "Hi\nHello\\World";
+
+    fn store(name: &str) -> (HistoryStore, PathBuf) {
+        let dir = env::temp_dir().join(format!("picow-history-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let file = dir.join("file.txt");
+        fs::write(&file, "Hello\\World").unwrap();
+        (HistoryStore::in_dir(dir.join("undo")), file)
+    }
+
+    fn history() -> UndoHistory {
+        let mut history = UndoHistory::default();
+        history.push(EditOp::insert((0, 5), "\\World"), ((0, 5), None), ((0, 11), None));
+        history.push(EditOp::Replace { from: (0, 0), inserted_lines: vecr!["Hi", ""], deleted_lines: vecr![""] }, ((0, 5), Some((0, 0))), ((1, 0), None));
+        history
+    }
+
+    #[test]
+    fn save_and_load() {
+        let (store, file) = store("roundtrip");
+        let file_name = file.to_str().unwrap();
+        let history = history();
+
+        store.save(file_name, CONTENT, &history).unwrap();
+        let loaded = store.load(file_name, CONTENT).unwrap().unwrap();
+
+        assert_eq!(loaded.current(), history.current());
+        assert_eq!(
+            loaded.nodes().iter().map(|node| (&node.step, node.parent())).collect::<Vec<_>>(),
+            history.nodes().iter().map(|node| (&node.step, node.parent())).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn changed_content_not_loaded() {
+        let (store, file) = store("changed");
+        let file_name = file.to_str().unwrap();
+
+        store.save(file_name, CONTENT, &history()).unwrap();
+        assert!(store.load(file_name, "Hi\nHello World").unwrap().is_none());
+    }
+
+    #[test]
+    fn missing_history() {
+        let (store, file) = store("missing");
+        assert!(store.load(file.to_str().unwrap(), "Hello").unwrap().is_none());
+    }
+
+    #[test]
+    fn ops_outside_content_not_loaded() {
+        let text = write(CONTENT, &history());
+        assert!(read(&text, CONTENT).is_some());
+        assert!(read(&text.replace("I 0:5 1", "I 0:50 1"), CONTENT).is_none());
+        assert!(read(&text.replace("\\\\World", "\\\\Earth"), CONTENT).is_none());
+    }
+
+    #[test]
+    fn large_history_keeps_newest_steps() {
+        let half = "a".repeat(MAX_FILE_SIZE / 2);
+        let mut history = UndoHistory::default();
+        for idx in 0..3 {
+            let from = (0, idx * half.len());
+            history.push(EditOp::insert(from, &half), (from, None), ((0, (idx + 1) * half.len()), None));
+        }
+
+        let content = half.repeat(3);
+        let loaded = read(&write(&content, &history), &content).unwrap();
+        assert_eq!((loaded.nodes().len(), loaded.current()), (2, 1));
+        assert_eq!(loaded.nodes()[1].step, history.nodes()[3].step);
+    }
+
+    #[test]
+    fn remove_history() {
+        let (store, file) = store("remove");
+        let file_name = file.to_str().unwrap();
+
+        store.save(file_name, CONTENT, &history()).unwrap();
+        store.remove(file_name).unwrap();
+        assert!(store.load(file_name, CONTENT).unwrap().is_none());
+        store.remove(file_name).unwrap();
+    }
+
+    #[test]
+    fn prune_old_and_oversized_files() {
+        let (store, _) = store("prune");
+        fs::create_dir_all(&store.dir).unwrap();
+        let now = SystemTime::now();
+        for (name, age) in [("new", 0), ("older", 10), ("oldest", 20), ("expired", 1000)] {
+            let file = fs::File::create(store.dir.join(format!("{}.undo", name))).unwrap();
+            file.set_len(10).unwrap();
+            file.set_modified(now - Duration::from_secs(age)).unwrap();
+        }
+        fs::write(store.dir.join("other.txt"), "keep").unwrap();
+
+        store.prune(Duration::from_secs(100), 25).unwrap();
+
+        let mut names: Vec<String> = fs::read_dir(&store.dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        assert_eq!(names, vec!["new.undo", "older.undo", "other.txt"]);
+    }
+
+    #[test]
+    fn escape_roundtrip() {
+        assert_eq!(unescape(&escape("a\\b\rc\nd")), "a\\b\rc\nd");
+    }
+}
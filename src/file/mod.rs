@@ -0,0 +1,10 @@
+mod winapi;
+
+pub mod config;
+pub mod diff;
+pub mod editorconfig;
+pub mod encoding;
+pub mod history;
+pub mod save;
+pub mod swap;
+pub mod watch;
@@ -0,0 +1,228 @@
+use std::{fs::{self, File, OpenOptions}, io::{self, ErrorKind, Write}, path::{Path, PathBuf}, process};
+
+use super::winapi;
+
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum Backup {
+    None,
+    SameDir,
+    Dir(PathBuf),
+}
+
+pub fn save(file_name: &str, content: &[u8], backup: &Backup) -> io::Result<()> {
+    let target = resolve(file_name);
+
+    if target.exists() {
+        check_writable(&target)?;
+        backup_file(&target, backup)?;
+
+        if is_hard_linked(&target)? {
+            return write_in_place(&target, content);
+        }
+    }
+
+    write_atomically(&target, content)
+}
+
+fn resolve(file_name: &str) -> PathBuf {
+    fs::canonicalize(file_name).unwrap_or_else(|_| PathBuf::from(file_name))
+}
+
+fn check_writable(target: &Path) -> io::Result<()> {
+    if fs::metadata(target)?.permissions().readonly() {
+        Err(io::Error::new(ErrorKind::PermissionDenied, "file is read-only"))
+    } else {
+        Ok(())
+    }
+}
+
+fn is_hard_linked(target: &Path) -> io::Result<bool> {
+    let file = File::open(target)?;
+    Ok(winapi::link_count(&file)? > 1)
+}
+
+
+fn write_atomically(target: &Path, content: &[u8]) -> io::Result<()> {
+    let temp = temp_path(target);
+    let file = match File::create(&temp) {
+        Ok(file) => file,
+        Err(_) => return write_in_place(target, content),
+    };
+
+    if let Err(err) = write_temp(file, &temp, target, content) {
+        let _ = fs::remove_file(&temp);
+        return Err(err);
+    }
+
+    fs::rename(&temp, target).or_else(|_| {
+        fs::remove_file(&temp)?;
+        write_in_place(target, content)
+    })
+}
+
+fn temp_path(target: &Path) -> PathBuf {
+    sibling(target, |name| format!(".{}.{}.tmp", name, process::id()))
+}
+
+fn write_temp(mut file: File, temp: &Path, target: &Path, content: &[u8]) -> io::Result<()> {
+    file.write_all(content)?;
+    file.sync_all()?;
+
+    if let Ok(metadata) = fs::metadata(target) {
+        fs::set_permissions(temp, metadata.permissions())?;
+    }
+    Ok(())
+}
+
+fn write_in_place(target: &Path, content: &[u8]) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).truncate(true).create(true).open(target)?;
+    file.write_all(content)?;
+    file.sync_all()
+}
+
+
+fn backup_file(target: &Path, backup: &Backup) -> io::Result<()> {
+    match backup_path(target, backup)? {
+        Some(backup_path) => fs::copy(target, backup_path).map(|_| ()),
+        None => Ok(())
+    }
+}
+
+fn backup_path(target: &Path, backup: &Backup) -> io::Result<Option<PathBuf>> {
+    match backup {
+        Backup::None => Ok(None),
+        Backup::SameDir => Ok(Some(sibling(target, |name| format!("{}~", name)))),
+        Backup::Dir(dir) => {
+            fs::create_dir_all(dir)?;
+            Ok(Some(dir.join(format!("{}~", flatten(target)))))
+        }
+    }
+}
+
+fn flatten(path: &Path) -> String {
+    path.to_string_lossy()
+        .trim_start_matches(r"\\?\")
+        .replace(['\\', '/', ':'], "%")
+}
+
+fn sibling<F>(path: &Path, name: F) -> PathBuf
+where
+    F: Fn(&str) -> String,
+{
+    let file_name = path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    path.with_file_name(name(&file_name))
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, path::PathBuf};
+
+    use super::*;
+
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("picow-save-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir).unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn save_replaces_content() {
+        let dir = test_dir("replace");
+        let file = dir.join("file.txt");
+        fs::write(&file, "Hello").unwrap();
+
+        save(file.to_str().unwrap(), b"World", &Backup::None).unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), "World");
+        assert_eq!(file_names(&dir), vec!["file.txt"]);
+    }
+
+    #[test]
+    fn save_new_file() {
+        let dir = test_dir("new");
+        let file = dir.join("file.txt");
+
+        save(file.to_str().unwrap(), b"Hello", &Backup::SameDir).unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), "Hello");
+        assert_eq!(file_names(&dir), vec!["file.txt"]);
+    }
+
+    #[test]
+    fn save_backup_same_dir() {
+        let dir = test_dir("backup-same-dir");
+        let file = dir.join("file.txt");
+        fs::write(&file, "Hello").unwrap();
+
+        save(file.to_str().unwrap(), b"World", &Backup::SameDir).unwrap();
+
+        assert_eq!(fs::read_to_string(dir.join("file.txt~")).unwrap(), "Hello");
+        assert_eq!(fs::read_to_string(&file).unwrap(), "World");
+    }
+
+    #[test]
+    fn save_backup_dir() {
+        let dir = test_dir("backup-dir");
+        let backup_dir = dir.join("backup");
+        let file = dir.join("file.txt");
+        fs::write(&file, "Hello").unwrap();
+
+        save(file.to_str().unwrap(), b"World", &Backup::Dir(backup_dir.clone())).unwrap();
+
+        let backups = file_names(&backup_dir);
+        assert_eq!(backups.len(), 1);
+        assert!(backups[0].ends_with("file.txt~"));
+        assert_eq!(fs::read_to_string(backup_dir.join(&backups[0])).unwrap(), "Hello");
+    }
+
+    #[test]
+    fn save_in_place_without_temp_file() {
+        let dir = test_dir("no-temp");
+        let file = dir.join("file.txt");
+        fs::write(&file, "Hello").unwrap();
+        fs::create_dir(temp_path(&fs::canonicalize(&file).unwrap())).unwrap();
+
+        save(file.to_str().unwrap(), b"World", &Backup::None).unwrap();
+
+        assert_eq!(fs::read_to_string(&file).unwrap(), "World");
+    }
+
+    #[test]
+    fn save_hard_link_in_place() {
+        let dir = test_dir("hard-link");
+        let file = dir.join("file.txt");
+        let link = dir.join("link.txt");
+        fs::write(&file, "Hello").unwrap();
+        fs::hard_link(&file, &link).unwrap();
+
+        save(file.to_str().unwrap(), b"World", &Backup::None).unwrap();
+
+        assert_eq!(fs::read_to_string(&link).unwrap(), "World");
+    }
+
+    #[test]
+    fn save_read_only_fails() {
+        let dir = test_dir("read-only");
+        let file = dir.join("file.txt");
+        fs::write(&file, "Hello").unwrap();
+        let mut permissions = fs::metadata(&file).unwrap().permissions();
+        permissions.set_readonly(true);
+        fs::set_permissions(&file, permissions).unwrap();
+
+        let result = save(file.to_str().unwrap(), b"World", &Backup::None);
+
+        assert_eq!(result.unwrap_err().kind(), ErrorKind::PermissionDenied);
+        assert_eq!(fs::read_to_string(&file).unwrap(), "Hello");
+    }
+}
@@ -0,0 +1,155 @@
+use std::{fs, io, path::{Path, PathBuf}, time::{Duration, Instant}};
+
+use crate::editor::content::EditorContent;
+use super::save::{self, Backup};
+
+const SWAP_INTERVAL: Duration = Duration::from_secs(4);
+const SWAP_EDITS: usize = 200;
+
+
+pub struct SwapFile {
+    path: PathBuf,
+    written_changes: usize,
+    written_at: Instant,
+}
+
+impl SwapFile {
+    pub fn new(file_name: &str) -> Self {
+        Self {
+            path: swap_path(Path::new(file_name)),
+            written_changes: 0,
+            written_at: Instant::now(),
+        }
+    }
+
+    pub fn update(&mut self, content: &EditorContent, changes: usize) -> io::Result<()> {
+        let pending = changes - self.written_changes;
+
+        if pending >= SWAP_EDITS || (pending > 0 && self.written_at.elapsed() >= SWAP_INTERVAL) {
+            self.write(&content.to_string(), changes)?;
+        }
+        Ok(())
+    }
+
+    fn write(&mut self, content: &str, changes: usize) -> io::Result<()> {
+        self.written_at = Instant::now();
+        save::save(&self.path.to_string_lossy(), content.as_bytes(), &Backup::None)?;
+        self.written_changes = changes;
+        Ok(())
+    }
+
+    pub fn saved(&mut self, changes: usize) -> io::Result<()> {
+        self.written_changes = changes;
+        self.remove()
+    }
+
+    pub fn remove(&self) -> io::Result<()> {
+        if self.path.exists() {
+            fs::remove_file(&self.path)?;
+        }
+        Ok(())
+    }
+
+    pub fn recoverable(&self, file_name: &str, content: &str) -> io::Result<Option<String>> {
+        if !self.path.exists() || !is_newer(&self.path, Path::new(file_name))? {
+            return Ok(None);
+        }
+
+        let swap_content = fs::read_to_string(&self.path)?;
+        if swap_content == content {
+            self.remove()?;
+            return Ok(None);
+        }
+
+        Ok(Some(swap_content))
+    }
+}
+
+fn swap_path(file: &Path) -> PathBuf {
+    let file_name = file.file_name().map(|n| n.to_string_lossy()).unwrap_or_default();
+    file.with_file_name(format!(".{}.picow.swp", file_name))
+}
+
+fn is_newer(file: &Path, than: &Path) -> io::Result<bool> {
+    Ok(fs::metadata(file)?.modified()? > fs::metadata(than)?.modified()?)
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, path::PathBuf, process, thread};
+
+    use super::*;
+
+    fn test_file(name: &str, content: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("picow-swap-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let file = dir.join("file.txt");
+        fs::write(&file, content).unwrap();
+        file
+    }
+
+    #[test]
+    fn swap_path_hidden_sibling() {
+        assert_eq!(swap_path(Path::new("dir/file.txt")), PathBuf::from("dir/.file.txt.picow.swp"));
+    }
+
+    #[test]
+    fn update_waits_for_enough_edits() {
+        let file = test_file("edits", "Hello");
+        let mut swap = SwapFile::new(file.to_str().unwrap());
+
+        let content = EditorContent::parse("Hello World");
+
+        swap.update(&content, 1).unwrap();
+        assert!(!swap.path.exists());
+
+        swap.update(&content, SWAP_EDITS).unwrap();
+        assert_eq!(fs::read_to_string(&swap.path).unwrap(), "Hello World");
+    }
+
+    #[test]
+    fn recoverable_newer_swap() {
+        let file = test_file("recover", "Hello");
+        let mut swap = SwapFile::new(file.to_str().unwrap());
+        thread::sleep(Duration::from_millis(20));
+        swap.write("Hello World", 1).unwrap();
+
+        let recovered = SwapFile::new(file.to_str().unwrap()).recoverable(file.to_str().unwrap(), "Hello").unwrap();
+        assert_eq!(recovered, Some("Hello World".to_string()));
+    }
+
+    #[test]
+    fn not_recoverable_same_content() {
+        let file = test_file("same", "Hello");
+        let mut swap = SwapFile::new(file.to_str().unwrap());
+        thread::sleep(Duration::from_millis(20));
+        swap.write("Hello", 1).unwrap();
+
+        assert_eq!(swap.recoverable(file.to_str().unwrap(), "Hello").unwrap(), None);
+        assert!(!swap.path.exists());
+    }
+
+    #[test]
+    fn compare_with_decoded_content() {
+        let file = test_file("decoded", "");
+        fs::write(&file, b"\xEF\xBB\xBFcaf\xC3\xA9").unwrap();
+        let mut swap = SwapFile::new(file.to_str().unwrap());
+        thread::sleep(Duration::from_millis(20));
+        swap.write("café", 1).unwrap();
+
+        assert_eq!(swap.recoverable(file.to_str().unwrap(), "café").unwrap(), None);
+    }
+
+    #[test]
+    fn saved_removes_swap() {
+        let file = test_file("saved", "Hello");
+        let mut swap = SwapFile::new(file.to_str().unwrap());
+        swap.write("Hello World", 1).unwrap();
+
+        swap.saved(1).unwrap();
+        assert!(!swap.path.exists());
+    }
+}
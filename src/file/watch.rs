@@ -0,0 +1,176 @@
+use std::{collections::hash_map::DefaultHasher, fs, hash::{Hash, Hasher}, io::{self, ErrorKind}, path::{Path, PathBuf}, time::{Duration, Instant, SystemTime}};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+
+#[derive(PartialEq, Debug)]
+pub enum FileChange {
+    Unchanged,
+    Modified,
+    Deleted,
+}
+
+#[derive(PartialEq, Debug)]
+struct FileStamp {
+    modified: SystemTime,
+    len: u64,
+    hash: u64,
+}
+
+pub struct FileWatch {
+    path: PathBuf,
+    stamp: Option<FileStamp>,
+    is_recorded: bool,
+    checked_at: Instant,
+}
+
+impl FileWatch {
+    pub fn new(file_name: &str) -> Self {
+        let mut watch = Self { path: PathBuf::from(file_name), stamp: None, is_recorded: false, checked_at: Instant::now() };
+        watch.record();
+        watch
+    }
+
+    pub fn record(&mut self) {
+        match stamp(&self.path) {
+            Ok(stamp) => { self.stamp = stamp; self.is_recorded = true },
+            Err(_) => self.is_recorded = false,
+        }
+    }
+
+    pub fn check_due(&mut self) -> FileChange {
+        if self.checked_at.elapsed() < CHECK_INTERVAL {
+            return FileChange::Unchanged;
+        }
+        self.check()
+    }
+
+    pub fn check(&mut self) -> FileChange {
+        self.checked_at = Instant::now();
+
+        if !self.is_recorded {
+            self.record();
+            return FileChange::Unchanged;
+        }
+        self.compare().unwrap_or(FileChange::Unchanged)
+    }
+
+    fn compare(&mut self) -> io::Result<FileChange> {
+        let metadata = match fs::metadata(&self.path) {
+            Ok(metadata) => metadata,
+            Err(err) if err.kind() == ErrorKind::NotFound => return Ok(self.deleted()),
+            Err(err) => return Err(err),
+        };
+
+        match self.stamp {
+            Some(ref stamp) if stamp.modified == metadata.modified()? && stamp.len == metadata.len() => Ok(FileChange::Unchanged),
+            Some(ref stamp) => {
+                let new_stamp = stamp_of(fs::read(&self.path)?, metadata.modified()?);
+                if new_stamp.hash == stamp.hash {
+                    self.stamp = Some(new_stamp);
+                    Ok(FileChange::Unchanged)
+                } else {
+                    Ok(FileChange::Modified)
+                }
+            },
+            None => Ok(FileChange::Modified),
+        }
+    }
+
+    fn deleted(&self) -> FileChange {
+        match self.stamp {
+            Some(_) => FileChange::Deleted,
+            None => FileChange::Unchanged,
+        }
+    }
+}
+
+fn stamp(path: &Path) -> io::Result<Option<FileStamp>> {
+    match fs::metadata(path) {
+        Ok(metadata) => Ok(Some(stamp_of(fs::read(path)?, metadata.modified()?))),
+        Err(err) if err.kind() == ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn stamp_of(bytes: Vec<u8>, modified: SystemTime) -> FileStamp {
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    FileStamp { modified, len: bytes.len() as u64, hash: hasher.finish() }
+}
+
+
+#[cfg(test)]
+mod test {
+    use std::{env, fs, process, thread};
+
+    use super::*;
+
+    fn test_file(name: &str, content: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("picow-watch-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let file = dir.join("file.txt");
+        fs::write(&file, content).unwrap();
+        file
+    }
+
+    fn watch(file: &Path) -> FileWatch {
+        FileWatch::new(file.to_str().unwrap())
+    }
+
+    #[test]
+    fn unchanged() {
+        let file = test_file("unchanged", "Hello");
+        assert_eq!(watch(&file).check(), FileChange::Unchanged);
+    }
+
+    #[test]
+    fn modified() {
+        let file = test_file("modified", "Hello");
+        let mut watch = watch(&file);
+        fs::write(&file, "Hello World").unwrap();
+
+        assert_eq!(watch.check(), FileChange::Modified);
+    }
+
+    #[test]
+    fn touched_is_unchanged() {
+        let file = test_file("touched", "Hello");
+        let mut watch = watch(&file);
+        thread::sleep(Duration::from_millis(20));
+        fs::write(&file, "Hello").unwrap();
+
+        assert_eq!(watch.check(), FileChange::Unchanged);
+    }
+
+    #[test]
+    fn deleted() {
+        let file = test_file("deleted", "Hello");
+        let mut watch = watch(&file);
+        fs::remove_file(&file).unwrap();
+
+        assert_eq!(watch.check(), FileChange::Deleted);
+    }
+
+    #[test]
+    fn unreadable_is_unchanged() {
+        let file = test_file("unreadable", "Hello");
+        let mut watch = watch(&file);
+        fs::remove_file(&file).unwrap();
+        fs::create_dir(&file).unwrap();
+
+        assert_eq!(watch.check(), FileChange::Unchanged);
+    }
+
+    #[test]
+    fn recorded_after_change() {
+        let file = test_file("recorded", "Hello");
+        let mut watch = watch(&file);
+        fs::write(&file, "Hello World").unwrap();
+        watch.record();
+
+        assert_eq!(watch.check(), FileChange::Unchanged);
+    }
+}
@@ -0,0 +1,23 @@
+use std::{fs::File, io::{self, Error}, os::windows::io::AsRawHandle};
+
+use winapi::um::{
+    fileapi::{GetFileInformationByHandle, BY_HANDLE_FILE_INFORMATION},
+    winnt::HANDLE
+};
+
+
+pub fn link_count(file: &File) -> io::Result<u32> {
+    with_file_info(file, |info| info.nNumberOfLinks)
+}
+
+fn with_file_info<T, F>(file: &File, extract: F) -> Result<T, Error> where F: FnOnce(BY_HANDLE_FILE_INFORMATION) -> T {
+    unsafe {
+        let mut info = BY_HANDLE_FILE_INFORMATION::default();
+
+        if GetFileInformationByHandle(file.as_raw_handle() as HANDLE, &mut info) == 0 {
+            return Err(Error::last_os_error());
+        }
+
+        Ok(extract(info))
+    }
+}
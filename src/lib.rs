@@ -1,3 +1,4 @@
 pub mod app;
 pub mod editor;
+pub mod file;
 pub mod terminal;
@@ -20,13 +20,25 @@ pub fn parse(input: &str) -> IResult<&str, Event> {
             alt((
                 cursor_key,
                 special_key,
-                mouse.map(Mouse),
+                char_key,
+                mouse.map(|(mouse, modifiers)| Mouse(mouse, modifiers)),
             ))
         ),
+        preceded(tag("\x1BO"), function_key).map(|key| Key(key, 0)),
+        preceded(tag("\x1B"), unicode_char).map(|c| Key(Char(c), ALT)),
+        preceded(tag("\x1B"), ctrl_char).map(|c| Key(Char(c), CTRL | ALT)),
         unicode_char.map(|c| Key(Char(c), 0)),
     ))(input)
 }
 
+pub fn with_key_record(event: Event, key: Option<Event>) -> Event {
+    match (event, key) {
+        (Key(Char(_) | Enter | Backspace, modifiers), Some(key @ Key(_, key_modifiers)))
+            if modifiers & CTRL != 0 && key_modifiers & CTRL != 0 => key,
+        (event, _) => event,
+    }
+}
+
 pub const BRACKETED_PASTE_START: &str = "\x1B[200~";
 pub const BRACKETED_PASTE_END: &str = "\x1B[201~";
 
@@ -47,8 +59,10 @@ fn special_char(input: &str) -> IResult<&str, Event> {
         eof.map(|_| Key(Char('Z'), CTRL)),
         preceded(tag("\x1B"), eof).map(|_| Key(Esc, 0)),
         tag("\u{7F}").map(|_| Key(Backspace, 0)),
+        tag("\x08").map(|_| Key(Backspace, CTRL)),
+        tag("\x1B\u{7F}").map(|_| Key(Backspace, ALT)),
         tag("\t").map(|_| Key(Tab, 0)),
-        tag("\n").map(|_| Key(Enter, 0)),
+        tag("\n").map(|_| Key(Char('J'), CTRL)),
         tag("\r").map(|_| Key(Enter, 0)),
         ctrl_char.map(|c| Key(Char(c), CTRL)),
     ))(input)
@@ -70,12 +84,22 @@ fn cursor_key(input: &str) -> IResult<&str, Event> {
             tag("D").map(|_| Left),
             tag("H").map(|_| Home),
             tag("F").map(|_| End),
+            function_key,
         ))
     ))
     .map(|(modifiers, key)| Key(key, modifiers))
     .parse(input)
 }
 
+fn function_key(input: &str) -> IResult<&str, KeyCode> {
+    alt((
+        tag("P").map(|_| F(1)),
+        tag("Q").map(|_| F(2)),
+        tag("R").map(|_| F(3)),
+        tag("S").map(|_| F(4)),
+    ))(input)
+}
+
 fn key_modifiers(s: Option<&str>) -> u8 {
     match s {
         Some("2") => SHIFT,
@@ -93,6 +117,14 @@ fn special_key(input: &str) -> IResult<&str, Event> {
     terminated(
         tuple((
             alt((
+                tag("15").map(|_| F(5)),
+                tag("17").map(|_| F(6)),
+                tag("18").map(|_| F(7)),
+                tag("19").map(|_| F(8)),
+                tag("20").map(|_| F(9)),
+                tag("21").map(|_| F(10)),
+                tag("23").map(|_| F(11)),
+                tag("24").map(|_| F(12)),
                 tag("2").map(|_| Insert),
                 tag("3").map(|_| Delete),
                 tag("5").map(|_| PageUp),
@@ -106,7 +138,32 @@ fn special_key(input: &str) -> IResult<&str, Event> {
     .parse(input)
 }
 
-fn mouse(input: &str) -> IResult<&str, Mouse> {
+fn char_key(input: &str) -> IResult<&str, Event> {
+    terminated(
+        tuple((
+            map_res(digit1, str::parse::<u32>),
+            opt(preceded(tag(";"), digit1)).map(key_modifiers),
+        )),
+        tag("u")
+    )
+    .map(|(code, modifiers)| Key(char_key_code(code, modifiers), modifiers))
+    .parse(input)
+}
+
+fn char_key_code(code: u32, modifiers: u8) -> KeyCode {
+    match code {
+        9 => Tab,
+        13 => Enter,
+        27 => Esc,
+        127 => Backspace,
+        _ => match char::from_u32(code).unwrap_or_default() {
+            c if modifiers & CTRL != 0 => Char(c.to_ascii_uppercase()),
+            c => Char(c),
+        }
+    }
+}
+
+fn mouse(input: &str) -> IResult<&str, (Mouse, u8)> {
     let (rest, (button, x, y, event)) = preceded(
         tag("<"),
         tuple((
@@ -117,6 +174,9 @@ fn mouse(input: &str) -> IResult<&str, Mouse> {
         )),
     )(input)?;
 
+    let modifiers = mouse_modifiers(button);
+    let button = button & !0b00011100;
+
     let mouse = match button {
         64 => WheelUp(x, y),
         65 => WheelDown(x, y),
@@ -139,7 +199,12 @@ fn mouse(input: &str) -> IResult<&str, Mouse> {
         ),
     };
 
-    Ok((rest, mouse))
+    Ok((rest, (mouse, modifiers)))
+}
+
+fn mouse_modifiers(button: u8) -> u8 {
+    let modifier = |bit: u8, modifier: u8| if button & bit != 0 { modifier } else { 0 };
+    modifier(0b00000100, SHIFT) | modifier(0b00001000, ALT) | modifier(0b00010000, CTRL)
 }
 
 
@@ -164,6 +229,8 @@ mod tests {
     parse!(key_ctrl_z: "" => Key(Char('Z'), CTRL));
 
     parse!(key_backspace: "\u{7F}" => Key(Backspace, 0));
+    parse!(key_alt_backspace: "\x1B\u{7F}" => Key(Backspace, ALT));
+    parse!(key_ctrl_alt_z: "\x1B\x1A" => Key(Char('Z'), CTRL | ALT));
     parse!(key_tab: "\t" => Key(Tab, 0));
     parse!(key_up: "\x1B[A" => Key(Up, 0));
     parse!(key_down:"\x1B[B" => Key(Down, 0));
@@ -190,20 +257,64 @@ mod tests {
     parse!(key_ctrl_page_up: "\x1B[5;5~" => Key(PageUp, CTRL));
     parse!(key_ctrl_alt_page_down: "\x1B[6;7~" => Key(PageDown, CTRL | ALT));
 
+    parse!(key_f1: "\x1BOP" => Key(F(1), 0));
+    parse!(key_f3: "\x1BOR" => Key(F(3), 0));
+    parse!(key_shift_f3: "\x1B[1;2R" => Key(F(3), SHIFT));
+    parse!(key_f5: "\x1B[15~" => Key(F(5), 0));
+    parse!(key_f12: "\x1B[24~" => Key(F(12), 0));
+    parse!(key_ctrl_f12: "\x1B[24;5~" => Key(F(12), CTRL));
+
+    parse!(key_ctrl_backspace: "\x08" => Key(Backspace, CTRL));
+    parse!(key_ctrl_j: "\n" => Key(Char('J'), CTRL));
+    parse!(key_csi_u_ctrl_enter: "\x1B[13;5u" => Key(Enter, CTRL));
+    parse!(key_ctrl_d: "\x04" => Key(Char('D'), CTRL));
+    parse!(key_ctrl_k: "\x0B" => Key(Char('K'), CTRL));
+    parse!(key_alt_n: "\x1Bn" => Key(Char('n'), ALT));
+    parse!(key_ctrl_shift_k: "\x1B[107;6u" => Key(Char('K'), CTRL | SHIFT));
+    parse!(key_ctrl_slash: "\x1F" => Key(Char('_'), CTRL));
+    parse!(key_alt_slash: "\x1B/" => Key(Char('/'), ALT));
+    parse!(key_ctrl_shift_slash: "\x1B[47;6u" => Key(Char('/'), CTRL | SHIFT));
+    parse!(key_alt_c: "\x1Bc" => Key(Char('c'), ALT));
+    parse!(key_alt_shift_c: "\x1BC" => Key(Char('C'), ALT));
+
     parse!(paste: "\x1B[200~Hello World!\x1B[201~" => Paste("Hello World!".to_string()));
 
-    parse!(mouse_left_press: "\x1B[<0;128;43M" => Mouse(Button(MouseButton::Left, Press, 128, 43)));
-    parse!(mouse_middle_press: "\x1B[<1;1;12M" => Mouse(Button(MouseButton::Middle, Press, 1, 12)));
-    parse!(mouse_right_press: "\x1B[<2;98;443M" => Mouse(Button(MouseButton::Right, Press, 98, 443)));
+    parse!(mouse_left_press: "\x1B[<0;128;43M" => Mouse(Button(MouseButton::Left, Press, 128, 43), 0));
+    parse!(mouse_middle_press: "\x1B[<1;1;12M" => Mouse(Button(MouseButton::Middle, Press, 1, 12), 0));
+    parse!(mouse_right_press: "\x1B[<2;98;443M" => Mouse(Button(MouseButton::Right, Press, 98, 443), 0));
 
-    parse!(mouse_left_drag: "\x1B[<32;128;43M" => Mouse(Button(MouseButton::Left, Drag, 128, 43)));
-    parse!(mouse_middle_drag: "\x1B[<33;1;12M" => Mouse(Button(MouseButton::Middle, Drag, 1, 12)));
-    parse!(mouse_right_drag: "\x1B[<34;98;443M" => Mouse(Button(MouseButton::Right, Drag, 98, 443)));
+    parse!(mouse_left_drag: "\x1B[<32;128;43M" => Mouse(Button(MouseButton::Left, Drag, 128, 43), 0));
+    parse!(mouse_middle_drag: "\x1B[<33;1;12M" => Mouse(Button(MouseButton::Middle, Drag, 1, 12), 0));
+    parse!(mouse_right_drag: "\x1B[<34;98;443M" => Mouse(Button(MouseButton::Right, Drag, 98, 443), 0));
 
-    parse!(mouse_left_release: "\x1B[<0;128;43m" => Mouse(Button(MouseButton::Left, Release, 128, 43)));
-    parse!(mouse_middle_release: "\x1B[<1;1;12m" => Mouse(Button(MouseButton::Middle, Release, 1, 12)));
-    parse!(mouse_right_release: "\x1B[<2;98;443m" => Mouse(Button(MouseButton::Right, Release, 98, 443)));
+    parse!(mouse_left_release: "\x1B[<0;128;43m" => Mouse(Button(MouseButton::Left, Release, 128, 43), 0));
+    parse!(mouse_middle_release: "\x1B[<1;1;12m" => Mouse(Button(MouseButton::Middle, Release, 1, 12), 0));
+    parse!(mouse_right_release: "\x1B[<2;98;443m" => Mouse(Button(MouseButton::Right, Release, 98, 443), 0));
 
-    parse!(mouse_wheel_up: "\x1B[<64;128;43M" => Mouse(WheelUp(128, 43)));
-    parse!(mouse_wheel_down: "\x1B[<65;1;12M" => Mouse(WheelDown(1, 12)));
+    parse!(mouse_wheel_up: "\x1B[<64;128;43M" => Mouse(WheelUp(128, 43), 0));
+    parse!(mouse_wheel_down: "\x1B[<65;1;12M" => Mouse(WheelDown(1, 12), 0));
+
+    parse!(mouse_alt_left_press: "\x1B[<8;10;4M" => Mouse(Button(MouseButton::Left, Press, 10, 4), ALT));
+    parse!(mouse_alt_left_drag: "\x1B[<40;10;4M" => Mouse(Button(MouseButton::Left, Drag, 10, 4), ALT));
+    parse!(mouse_ctrl_shift_wheel_up: "\x1B[<84;1;1M" => Mouse(WheelUp(1, 1), CTRL | SHIFT));
+
+    #[test]
+    fn key_record_tells_ctrl_h_from_ctrl_backspace() {
+        assert_eq!(with_key_record(Key(Backspace, CTRL), Some(Key(Char('H'), CTRL))), Key(Char('H'), CTRL));
+        assert_eq!(with_key_record(Key(Backspace, CTRL), Some(Key(Backspace, CTRL))), Key(Backspace, CTRL));
+        assert_eq!(with_key_record(Key(Backspace, CTRL), None), Key(Backspace, CTRL));
+    }
+
+    #[test]
+    fn key_record_tells_ctrl_enter_from_ctrl_j() {
+        assert_eq!(with_key_record(Key(Char('J'), CTRL), Some(Key(Enter, CTRL))), Key(Enter, CTRL));
+        assert_eq!(with_key_record(Key(Char('J'), CTRL), Some(Key(Enter, CTRL | SHIFT))), Key(Enter, CTRL | SHIFT));
+        assert_eq!(with_key_record(Key(Char('J'), CTRL), Some(Key(Char('J'), CTRL))), Key(Char('J'), CTRL));
+    }
+
+    #[test]
+    fn key_record_ignored_without_ctrl() {
+        assert_eq!(with_key_record(Key(Char('h'), 0), Some(Key(Char('H'), CTRL))), Key(Char('h'), 0));
+        assert_eq!(with_key_record(Key(Up, CTRL), Some(Key(Char('H'), CTRL))), Key(Up, CTRL));
+    }
 }
@@ -1,4 +1,4 @@
-use crate::terminal::commands::Command::{self, *};
+use crate::terminal::commands::{Command::{self, *}, CursorShape};
 
 macro_rules! csi {
     ($($arg:tt)*) => {{
@@ -19,6 +19,7 @@ pub fn ansi(command: &Command) -> String {
         ClearToEndOfLine => csi!("K"),
         Print(s) => s.to_string(),
         SetBackgroundColor(color) => csi!("{}m", color),
+        SetForegroundColor(color) => csi!("{}m", color),
 
         MoveTo(x, y) => csi!("{};{}H", y, x),
         MoveUp(n) => csi!("{}A", n),
@@ -27,6 +28,11 @@ pub fn ansi(command: &Command) -> String {
         MoveLeft(n) => csi!("{}D", n),
         HideCursor => csi!("?25l"),
         ShowCursor => csi!("?25h"),
+        SetCursorShape(shape) => csi!("{} q", match shape {
+            CursorShape::Default => 0,
+            CursorShape::Block => 2,
+            CursorShape::Bar => 6,
+        }),
 
         EnterAlternateScreen => csi!("?1049h{}", ansi(&MoveTo(1  , 1))),
         LeaveAlternateScreen => csi!("?1049l"),
@@ -4,6 +4,7 @@ pub enum Command {
     ClearToEndOfLine,
     Print(String),
     SetBackgroundColor(u8),
+    SetForegroundColor(u8),
 
     MoveTo(u16, u16),
     MoveUp(u16),
@@ -12,6 +13,7 @@ pub enum Command {
     MoveDown(u16),
     HideCursor,
     ShowCursor,
+    SetCursorShape(CursorShape),
 
     EnterAlternateScreen,
     LeaveAlternateScreen,
@@ -24,3 +26,9 @@ pub enum Command {
 
     SetWindowTitle(String),
 }
+
+pub enum CursorShape {
+    Default,
+    Block,
+    Bar,
+}
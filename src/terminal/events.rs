@@ -1,7 +1,7 @@
 #[derive(Debug, PartialEq)]
 pub enum Event {
     Key(KeyCode, u8),
-    Mouse(Mouse),
+    Mouse(Mouse, u8),
     Paste(String),
 }
 
@@ -22,6 +22,7 @@ pub enum KeyCode {
     End,
     PageUp,
     PageDown,
+    F(u8),
 }
 
 pub const CTRL: u8 = 0b0001;
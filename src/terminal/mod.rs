@@ -7,9 +7,9 @@ mod winapi;
 pub mod commands;
 pub mod events;
 
-use std::io;
+use std::{io, time::Duration};
 
-use self::{buffer::CommandBuffer, commands::Command::{self, *}, events::Event};
+use self::{buffer::CommandBuffer, commands::{Command::{self, *}, CursorShape}, events::Event};
 
 
 pub fn on_alternate_screen(window_title: &str, run: impl FnOnce() -> io::Result<()>) -> io::Result<()> {
@@ -33,6 +33,7 @@ fn init_alternate_screen(window_title: &str) -> io::Result<u32> {
 
 fn close_alternate_screen(console_mode: u32) -> io::Result<()> {
     vec!(
+        SetCursorShape(CursorShape::Default),
         DisableBracketedPaste,
         DisableMouseCapture,
         LeaveAlternateScreen
@@ -47,7 +48,16 @@ pub fn terminal_size() -> io::Result<(u16, u16)> {
 }
 
 pub fn read_event() -> io::Result<Event> {
-    reader::read_event()
+    let key = winapi::peek_key()?;
+    reader::read_event().map(|event| ansi_in::with_key_record(event, key))
+}
+
+pub fn poll_event(timeout: Duration) -> io::Result<Option<Event>> {
+    if winapi::wait_for_input(timeout)? {
+        read_event().map(Some)
+    } else {
+        Ok(None)
+    }
 }
 
 pub fn output(commands: Vec<Command>) -> io::Result<()> {
@@ -1,13 +1,19 @@
-use std::io::{self, Error};
+use std::{io::{self, Error}, time::{Duration, Instant}};
 
-use winapi::um::{
-    consoleapi::{GetConsoleMode, SetConsoleMode},
+use winapi::{shared::winerror::WAIT_TIMEOUT, um::{
+    consoleapi::{GetConsoleMode, ReadConsoleInputW, SetConsoleMode},
     handleapi::INVALID_HANDLE_VALUE,
     processenv::GetStdHandle,
-    winbase::{STD_INPUT_HANDLE, STD_OUTPUT_HANDLE},
-    wincon::{GetConsoleScreenBufferInfo, CONSOLE_SCREEN_BUFFER_INFO, ENABLE_AUTO_POSITION, ENABLE_MOUSE_INPUT, ENABLE_VIRTUAL_TERMINAL_INPUT, ENABLE_WINDOW_INPUT},
+    synchapi::WaitForSingleObject,
+    winbase::{STD_INPUT_HANDLE, STD_OUTPUT_HANDLE, WAIT_OBJECT_0},
+    wincon::{
+        GetConsoleScreenBufferInfo, PeekConsoleInputW, CONSOLE_SCREEN_BUFFER_INFO, ENABLE_AUTO_POSITION, ENABLE_MOUSE_INPUT, ENABLE_VIRTUAL_TERMINAL_INPUT, ENABLE_WINDOW_INPUT, INPUT_RECORD, KEY_EVENT,
+        LEFT_ALT_PRESSED, LEFT_CTRL_PRESSED, RIGHT_ALT_PRESSED, RIGHT_CTRL_PRESSED, SHIFT_PRESSED
+    },
     winnt::HANDLE
-};
+}};
+
+use super::events::{Event::{self, Key}, KeyCode::{Backspace, Char, Enter}, ALT, CTRL, SHIFT};
 
 
 const CONSOLE_MODE: u32 =
@@ -16,6 +22,9 @@ const CONSOLE_MODE: u32 =
     ENABLE_AUTO_POSITION |
     ENABLE_VIRTUAL_TERMINAL_INPUT;
 
+const VK_BACK: u16 = 0x08;
+const VK_RETURN: u16 = 0x0D;
+
 pub fn init_console() -> io::Result<u32> {
     let handle = get_std_in_handle()?;
     let mode = get_console_mode(handle)?;
@@ -49,6 +58,82 @@ fn get_std_in_handle() -> io::Result<HANDLE> {
     }
 }
 
+
+pub fn wait_for_input(timeout: Duration) -> io::Result<bool> {
+    let handle = get_std_in_handle()?;
+    let deadline = Instant::now() + timeout;
+
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if !wait_for_handle(handle, remaining)? {
+            return Ok(false);
+        }
+
+        let records = peek_input_records(handle)?;
+        if records.iter().any(is_key_down) {
+            return Ok(true);
+        }
+
+        // key releases, focus and resize records never reach stdin, drop them so the wait blocks again
+        discard_input_records(handle, records.len())?;
+    }
+}
+
+pub fn peek_key() -> io::Result<Option<Event>> {
+    let records = peek_input_records(get_std_in_handle()?)?;
+    Ok(records.iter().find(|record| is_key_down(record)).and_then(key_event))
+}
+
+fn key_event(record: &INPUT_RECORD) -> Option<Event> {
+    let key = unsafe { record.Event.KeyEvent() };
+    let code = match key.wVirtualKeyCode {
+        VK_BACK => Backspace,
+        VK_RETURN => Enter,
+        key @ 0x41..=0x5A => Char(key as u8 as char),
+        _ => return None
+    };
+
+    let modifiers = [(CTRL, LEFT_CTRL_PRESSED | RIGHT_CTRL_PRESSED), (ALT, LEFT_ALT_PRESSED | RIGHT_ALT_PRESSED), (SHIFT, SHIFT_PRESSED)]
+        .into_iter()
+        .filter(|&(_, pressed)| key.dwControlKeyState & pressed != 0)
+        .fold(0, |modifiers, (modifier, _)| modifiers | modifier);
+    Some(Key(code, modifiers))
+}
+
+fn wait_for_handle(handle: HANDLE, timeout: Duration) -> io::Result<bool> {
+    unsafe {
+        match WaitForSingleObject(handle, timeout.as_millis() as u32) {
+            WAIT_OBJECT_0 => Ok(true),
+            WAIT_TIMEOUT => Ok(false),
+            _ => Err(Error::last_os_error())
+        }
+    }
+}
+
+fn peek_input_records(handle: HANDLE) -> io::Result<Vec<INPUT_RECORD>> {
+    unsafe {
+        let mut records = vec![INPUT_RECORD::default(); 128];
+        let mut read: u32 = 0;
+        let res = PeekConsoleInputW(handle, records.as_mut_ptr(), records.len() as u32, &mut read);
+        records.truncate(read as usize);
+        result(res, 0, records)
+    }
+}
+
+fn discard_input_records(handle: HANDLE, count: usize) -> io::Result<()> {
+    unsafe {
+        let mut records = vec![INPUT_RECORD::default(); count];
+        let mut read: u32 = 0;
+        result(ReadConsoleInputW(handle, records.as_mut_ptr(), count as u32, &mut read), 0, ())
+    }
+}
+
+fn is_key_down(record: &INPUT_RECORD) -> bool {
+    unsafe {
+        record.EventType == KEY_EVENT && record.Event.KeyEvent().bKeyDown != 0
+    }
+}
+
 fn result<T: std::cmp::PartialEq, U>(value: T, err_value: T, ret_value: U) -> io::Result<U> {
     if value == err_value {
         Err(Error::last_os_error())
@@ -0,0 +1,62 @@
+#[macro_use]
+#[path ="./edit_test_parse.rs"]
+mod edit_test_parse;
+
+
+mod comment_test {
+    use super::edit_test_parse::{assert, state};
+
+    use picow::editor::{events::process_event, languages::{BATCH, RUST}, state::EditorState};
+    use picow::terminal::events::{Event::{self, Key}, KeyCode::*, ALT, CTRL};
+
+    fn process(state: &mut EditorState, events: Vec<Event>) {
+        events.iter().for_each(|event| { process_event(event, state); });
+    }
+
+    #[test]
+    fn comment_current_line() {
+        let mut state = state(vec!["fn main() {", "    let▮ a = 1;", "}"]);
+        state.set_language(Some(&RUST));
+        process(&mut state, vec![Key(Char('_'), CTRL)]);
+
+        assert(&state, vec!["fn main() {", "    // let▮ a = 1;", "}"]);
+    }
+
+    #[test]
+    fn comment_selected_lines_and_keep_selection() {
+        let mut state = state(vec!["  ▯a", "    b", "  c▮"]);
+        state.set_language(Some(&RUST));
+        process(&mut state, vec![Key(Char('_'), CTRL)]);
+
+        assert(&state, vec!["  // ▯a", "  //   b", "  // c▮"]);
+    }
+
+    #[test]
+    fn uncomment_in_single_undo_step() {
+        let mut state = state(vec!["▯REM a", "REM b▮"]);
+        state.set_language(Some(&BATCH));
+        process(&mut state, vec![Key(Char('_'), CTRL)]);
+        assert(&state, vec!["▯a", "b▮"]);
+
+        process(&mut state, vec![Key(Char('Z'), CTRL)]);
+        assert(&state, vec!["▯REM a", "REM b▮"]);
+    }
+
+    #[test]
+    fn toggle_block_comment() {
+        let mut state = state(vec!["let ▯a = 1▮;"]);
+        state.set_language(Some(&RUST));
+        process(&mut state, vec![Key(Char('/'), ALT)]);
+        assert(&state, vec!["let ▯/*a = 1*/▮;"]);
+
+        process(&mut state, vec![Key(Char('/'), ALT)]);
+        assert(&state, vec!["let ▯a = 1▮;"]);
+    }
+
+    #[test]
+    fn no_comment_without_language() {
+        let mut state = state(vec!["▮a"]);
+        process(&mut state, vec![Key(Char('_'), CTRL)]);
+        assert(&state, vec!["▮a"]);
+    }
+}
@@ -6,8 +6,8 @@ mod edit_test_parse;
 mod edit_test {
     use super::edit_test_parse::{assert, state};
 
-    use picow::editor::events::process_event;
-    use picow::terminal::events::{Event::{Key, Paste}, KeyCode::*, CTRL};
+    use picow::editor::{events::process_event, settings::Settings};
+    use picow::terminal::events::{Event::{Key, Paste}, KeyCode::*, ALT, CTRL, SHIFT};
 
     edit_test!(
         test_insert_char:
@@ -94,7 +94,28 @@ mod edit_test {
         Key(Char('e'), 0),
         Key(Char('y'), 0),
         Key(Char('Z'), CTRL);
-        "HelloHe▮"
+        "Hello▮"
+    );
+
+    edit_test!(
+        undo_word:
+        "Hello▮";
+        Key(Char(' '), 0),
+        Key(Char('H'), 0),
+        Key(Char('e'), 0),
+        Key(Char('Z'), CTRL);
+        "Hello ▮"
+    );
+
+    edit_test!(
+        undo_after_cursor_move:
+        "Hello▮";
+        Key(Char('H'), 0),
+        Key(Left, 0),
+        Key(Right, 0),
+        Key(Char('e'), 0),
+        Key(Char('Z'), CTRL);
+        "HelloH▮"
     );
 
     edit_test!(
@@ -105,8 +126,366 @@ mod edit_test {
         Key(Char('y'), 0),
         Key(Char('Z'), CTRL),
         Key(Char('Z'), CTRL),
-        Key(Char('Z'), CTRL),
         Key(Char('Y'), CTRL);
-        "HelloH▮"
+        "HelloHey▮"
+    );
+
+    edit_test!(
+        test_ctrl_backspace_deletes_word:
+        "Hello wonderful▮ world";
+        Key(Backspace, CTRL);
+        "Hello ▮ world"
+    );
+
+    edit_test!(
+        test_ctrl_delete_deletes_word:
+        "Hello ▮wonderful world";
+        Key(Delete, CTRL);
+        "Hello ▮world"
+    );
+
+    edit_test!(
+        test_alt_backspace_joins_lines:
+        "Hello", "▮World";
+        Key(Backspace, ALT);
+        "Hello▮World"
+    );
+
+    edit_test!(
+        undo_branch_kept:
+        "Hello▮";
+        Key(Char('!'), 0),
+        Key(Char('Z'), CTRL),
+        Key(Char('?'), 0),
+        Key(Char('Z'), CTRL | ALT);
+        "Hello!▮"
+    );
+
+    edit_test!(
+        undo_browser_goes_to_state:
+        "Hello▮";
+        Key(Char('!'), 0),
+        Key(Char('Z'), CTRL),
+        Key(Char('?'), 0),
+        Key(Char('u'), ALT),
+        Key(Down, 0),
+        Key(Down, 0),
+        Key(Enter, 0);
+        "Hello▮"
+    );
+
+    #[test]
+    fn replace_content_keeps_cursor() {
+        let mut state = state(vec!["Hello", "Wor▮ld"]);
+
+        state.replace_content("Hello\nWonderful\nWorld");
+        assert(&state, vec!["Hello", "Won▮derful", "World"]);
+
+        process_event(&Key(Char('Z'), CTRL), &mut state);
+        assert_eq!(state.content.to_string(), "Hello\nWorld");
+    }
+
+    #[test]
+    fn clean_up_whitespace_trims_and_adds_final_newline() {
+        let mut state = state(vec!["Hello  ", "Wor▮ld\t"]);
+
+        assert!(state.clean_up_whitespace(true, true));
+        assert(&state, vec!["Hello", "Wor▮ld", ""]);
+        assert!(!state.clean_up_whitespace(true, true));
+    }
+
+    edit_test!(
+        tab_inserts_spaces_to_next_stop:
+        "ab▮c";
+        Key(Tab, 0);
+        "ab  ▮c"
+    );
+
+    edit_test!(
+        set_indent_to_tabs:
+        "▮a";
+        Key(Char('i'), ALT), Key(Char('t'), 0), Key(Enter, 0), Key(Tab, 0);
+        "\t▮a"
+    );
+
+    edit_test!(
+        invalid_indent_keeps_prompt_open:
+        "▮a";
+        Key(Char('i'), ALT), Key(Char('0'), 0), Key(Enter, 0), Key(Esc, 0), Key(Tab, 0);
+        "    ▮a"
+    );
+
+    edit_test!(
+        overwrite_replaces_chars:
+        "He▮llo";
+        Key(Insert, 0), Key(Char('a'), 0), Key(Char('r'), 0);
+        "Hear▮o"
+    );
+
+    edit_test!(
+        overwrite_inserts_at_line_end:
+        "Hel▮lo", "World";
+        Key(Insert, 0), Key(Char('p'), 0), Key(Char('!'), 0), Key(Char('!'), 0);
+        "Help!!▮", "World"
+    );
+
+    edit_test!(
+        overwrite_undo_restores_char:
+        "He▮llo";
+        Key(Insert, 0), Key(Char('a'), 0), Key(Char('Z'), CTRL);
+        "He▮llo"
+    );
+
+    edit_test!(
+        overwrite_undo_restores_word:
+        "He▮llo world";
+        Key(Insert, 0), Key(Char('a'), 0), Key(Char('b'), 0), Key(Char('c'), 0), Key(Char('Z'), CTRL);
+        "He▮llo world"
+    );
+
+    edit_test!(
+        insert_toggles_back:
+        "He▮llo";
+        Key(Insert, 0), Key(Insert, 0), Key(Char('a'), 0);
+        "Hea▮llo"
+    );
+
+    edit_test!(
+        enter_keeps_indent:
+        "    let a = 1;▮";
+        Key(Enter, 0);
+        "    let a = 1;", "    ▮"
+    );
+
+    edit_test!(
+        enter_indents_after_bracket:
+        "  fn main() {▮}";
+        Key(Enter, 0);
+        "  fn main() {", "      ▮}"
+    );
+
+    edit_test!(
+        backspace_removes_indent_level:
+        "      ▮foo";
+        Key(Backspace, 0), Key(Backspace, 0);
+        "▮foo"
+    );
+
+    edit_test!(
+        jump_to_matching_bracket:
+        "fn main() ▮{", "    call(a[0]);", "}";
+        Key(Char(']'), CTRL);
+        "fn main() {", "    call(a[0]);", "▮}"
     );
+
+    edit_test!(
+        jump_back_to_opening_bracket:
+        "f(a, (b))▮";
+        Key(Char(']'), CTRL);
+        "f▮(a, (b))"
+    );
+
+    edit_test!(
+        auto_close_bracket:
+        "call▮";
+        Key(Char('('), 0), Key(Char('x'), 0);
+        "call(x▮)"
+    );
+
+    edit_test!(
+        step_over_closer:
+        "call▮";
+        Key(Char('('), 0), Key(Char(')'), 0), Key(Char(';'), 0);
+        "call();▮"
+    );
+
+    edit_test!(
+        backspace_deletes_empty_pair:
+        "a = ▮";
+        Key(Char('"'), 0), Key(Backspace, 0);
+        "a = ▮"
+    );
+
+    #[test]
+    fn settings_disable_auto_close_and_auto_indent() {
+        let mut state = state(vec!["  if a {▮"]);
+        state.apply_settings(Settings { auto_close_pairs: false, auto_indent: false, ..Settings::default() });
+
+        process_event(&Key(Enter, 0), &mut state);
+        process_event(&Key(Char('('), 0), &mut state);
+        assert(&state, vec!["  if a {", "(▮"]);
+    }
+
+    #[test]
+    fn settings_keep_indent_override() {
+        let mut state = state(vec!["▮a"]);
+        for event in [Key(Char('i'), ALT), Key(Char('t'), 0), Key(Enter, 0)] {
+            process_event(&event, &mut state);
+        }
+        state.apply_settings(Settings::default());
+
+        process_event(&Key(Tab, 0), &mut state);
+        assert(&state, vec!["\t▮a"]);
+    }
+
+    edit_test!(
+        no_auto_close_before_word:
+        "▮foo";
+        Key(Char('['), 0);
+        "[▮foo"
+    );
+
+    edit_test!(
+        surround_selection:
+        "let ▯foo▮ = 1;";
+        Key(Char('('), 0), Key(Char('"'), 0);
+        "let (\"▯foo▮\") = 1;"
+    );
+
+    edit_test!(
+        duplicate_line:
+        "a", "b▮c", "d";
+        Key(Char('D'), CTRL);
+        "a", "bc", "b▮c", "d"
+    );
+
+    edit_test!(
+        duplicate_selected_lines:
+        "▯a", "b▮c";
+        Key(Char('D'), CTRL);
+        "a", "bc", "▯a", "b▮c"
+    );
+
+    edit_test!(
+        move_lines_up_keeps_selection:
+        "a", "b", "▯c", "d▮", "e";
+        Key(Up, ALT), Key(Up, ALT);
+        "▯c", "d▮", "a", "b", "e"
+    );
+
+    edit_test!(
+        move_lines_down_with_selection_to_last_line:
+        "▯a", "b", "▮c";
+        Key(Down, ALT);
+        "c", "▯a", "b▮"
+    );
+
+    edit_test!(
+        move_lines_up_with_selection_to_last_line:
+        "a", "▯b", "▮c";
+        Key(Up, ALT);
+        "▯b", "▮a", "c"
+    );
+
+    edit_test!(
+        move_line_down:
+        "a▮", "b";
+        Key(Down, ALT), Key(Down, ALT);
+        "b", "a▮"
+    );
+
+    edit_test!(
+        delete_line:
+        "abc", "d▮ef", "g";
+        Key(Char('K'), CTRL | SHIFT);
+        "abc", "g▮"
+    );
+
+    edit_test!(
+        delete_last_lines:
+        "abc", "▯def", "g▮";
+        Key(Char('K'), CTRL | SHIFT);
+        "a▮bc"
+    );
+
+    edit_test!(
+        ctrl_k_keeps_line:
+        "abc", "d▮ef";
+        Key(Char('K'), CTRL);
+        "abc", "d▮ef"
+    );
+
+    edit_test!(
+        join_lines_collapses_whitespace:
+        "let a =  ▮", "    1;";
+        Key(Char('J'), CTRL);
+        "let a = ▮1;"
+    );
+
+    edit_test!(
+        join_selected_lines:
+        "▯a", "  b", "  c▮";
+        Key(Char('J'), CTRL);
+        "▯a b c▮"
+    );
+
+    edit_test!(
+        insert_line_below:
+        "    fo▮o", "bar";
+        Key(Enter, CTRL);
+        "    foo", "    ▮", "bar"
+    );
+
+    edit_test!(
+        insert_line_above:
+        "    fo▮o";
+        Key(Enter, CTRL | SHIFT);
+        "    ▮", "    foo"
+    );
+
+    edit_test!(
+        transform_whole_document:
+        "b", "c▮", "a";
+        Key(Char('T'), CTRL), Paste(s!["sort"]), Key(Enter, 0);
+        "a", "b▮", "c"
+    );
+
+    edit_test!(
+        transform_keeps_final_newline_last:
+        "b", "c▮", "a", "";
+        Key(Char('T'), CTRL), Paste(s!["sort"]), Key(Enter, 0);
+        "a", "b▮", "c", ""
+    );
+
+    edit_test!(
+        transform_selection_keeps_selection:
+        "let ▯user_name▮ = 1;";
+        Key(Char('T'), CTRL), Paste(s!["camel"]), Key(Enter, 0);
+        "let ▯userName▮ = 1;"
+    );
+
+    edit_test!(
+        transform_single_undo:
+        "b", "a▮";
+        Key(Char('T'), CTRL), Paste(s!["reverse"]), Key(Enter, 0), Key(Char('Z'), CTRL);
+        "b", "a▮"
+    );
+
+    edit_test!(
+        filter_selection_through_command:
+        "▯b", "a", "b▮", "c";
+        Key(Char('E'), CTRL), Paste(s!["sort"]), Key(Enter, 0);
+        "▯a", "b", "b▮", "c"
+    );
+
+    edit_test!(
+        insert_command_output:
+        "a ▮b";
+        Key(Char('e'), ALT), Paste(s!["echo x"]), Key(Enter, 0);
+        "a x▮b"
+    );
+
+    #[test]
+    fn failing_command_keeps_buffer() {
+        let mut state = state(vec!["▮a", "b"]);
+        for event in [Key(Char('E'), CTRL), Paste(s!["exit 2"]), Key(Enter, 0)] {
+            process_event(&event, &mut state);
+        }
+        assert!(state.overlay.is_some());
+        assert(&state, vec!["▮a", "b"]);
+
+        process_event(&Key(Char('x'), 0), &mut state);
+        assert!(state.overlay.is_none());
+        assert(&state, vec!["▮a", "b"]);
+    }
 }
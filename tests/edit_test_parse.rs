@@ -2,7 +2,7 @@
 #[path ="./edit_test_macros.rs"]
 mod edit_test_macros;
 
-use picow::editor::{content::{EditorContent, LF}, row::Row, state::EditorState, viewport::Viewport};
+use picow::editor::{content::{EditorContent, LF}, pos::{PosInDocument, PosInDocumentExt}, row::Row, state::{EditorState, Selection}, viewport::Viewport};
 
 pub fn state(lines: Vec<&str>) -> EditorState {
     let (rows, cursor, selection_pos) = parse_rows(lines);
@@ -50,9 +50,14 @@ fn parse_row(input: &str) -> (Row, Option<usize>, Option<usize>) {
 }
 
 pub fn assert(state: &EditorState, lines: Vec<&str>) {
-    let (rows, cursor, _) = parse_rows(lines);
+    let (rows, cursor, selection_pos) = parse_rows(lines);
     assert_eq!(state.content.lines, rows);
     assert_eq!(state.cursor.pos(), cursor);
+    assert_eq!(state.selection(), selection(cursor, selection_pos));
+}
+
+fn selection(cursor: PosInDocument, selection_pos: Option<PosInDocument>) -> Selection {
+    selection_pos.map(|pos| if pos.is_before(&cursor) { (pos, cursor) } else { (cursor, pos) })
 }
 
 
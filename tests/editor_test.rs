@@ -162,7 +162,7 @@ mod move_horizoval {
 
     test_editor!(
         move_to_line_start
-        Key(Home, 0);
+        Key(Home, 0), Key(Home, 0);
         "┌───────────┐",
         "▯_____▮     │",
         "│______     │",
@@ -0,0 +1,114 @@
+#[macro_use]
+#[path ="./edit_test_parse.rs"]
+mod edit_test_parse;
+
+
+mod find_test {
+    use super::edit_test_parse::{assert, state};
+
+    use picow::editor::{events::process_event, state::EditorState};
+    use picow::terminal::events::{Event::{self, Key, Paste}, KeyCode::*, ALT, CTRL, SHIFT};
+
+    edit_test!(
+        test_find_from_cursor:
+        "Hello ▮World", "world wide";
+        Key(Char('F'), CTRL), Key(Char('w'), 0), Key(Char('o'), 0), Key(Enter, 0);
+        "Hello ▯Wo▮rld", "world wide"
+    );
+
+    edit_test!(
+        test_find_next:
+        "Hello ▮World", "world wide";
+        Key(Char('F'), CTRL), Key(Char('w'), 0), Key(Char('o'), 0), Key(Enter, 0), Key(F(3), 0);
+        "Hello World", "▯wo▮rld wide"
+    );
+
+    edit_test!(
+        test_find_prev_wraps:
+        "Hello ▮World", "world wide";
+        Key(Char('F'), CTRL), Key(Char('w'), 0), Key(Char('o'), 0), Key(Enter, 0), Key(F(3), SHIFT);
+        "Hello World", "▯wo▮rld wide"
+    );
+
+    edit_test!(
+        test_find_case_sensitive:
+        "Hello ▮World", "world wide";
+        Key(Char('F'), CTRL), Key(Char('c'), ALT), Key(Char('w'), 0), Key(Enter, 0);
+        "Hello World", "▯w▮orld wide"
+    );
+
+    edit_test!(
+        test_find_esc_restores_cursor:
+        "Hello ▮World", "world wide";
+        Key(Char('F'), CTRL), Key(Char('w'), 0), Key(Char('i'), 0), Key(Esc, 0);
+        "Hello ▮World", "world wide"
+    );
+
+    edit_test!(
+        test_find_prefilled_from_selection:
+        "▯Hel▮lo", "Hello";
+        Key(Char('F'), CTRL), Key(Enter, 0), Key(F(3), 0);
+        "Hello", "▯Hel▮lo"
+    );
+
+    edit_test!(
+        test_replace_next:
+        "▮Hello World", "world wide";
+        Key(Char('H'), CTRL), Key(Char('w'), 0), Key(Char('o'), 0), Key(Enter, 0),
+        Key(Char('E'), 0), Key(Enter, 0);
+        "Hello Erld", "▯wo▮rld wide"
+    );
+
+    edit_test!(
+        test_replace_skip:
+        "▮Hello World", "world wide";
+        Key(Char('H'), CTRL), Key(Char('w'), 0), Key(Char('o'), 0), Key(Enter, 0),
+        Key(Char('E'), 0), Key(F(3), 0), Key(Enter, 0);
+        "Hello ▯Wo▮rld", "Erld wide"
+    );
+
+    edit_test!(
+        test_replace_all_regex_captures:
+        "▮Hello World", "world wide";
+        Key(Char('H'), CTRL), Key(Char('r'), ALT), Paste(s!["(w)(o)"]), Key(Enter, 0),
+        Paste(s!["$2$1"]), Key(Char('a'), ALT);
+        "Hello oW▮rld", "owrld wide"
+    );
+
+    edit_test!(
+        test_replace_all_single_undo:
+        "▮Hello World", "world wide";
+        Key(Char('H'), CTRL), Key(Char('o'), 0), Key(Enter, 0), Key(Char('0'), 0), Key(Char('a'), ALT),
+        Key(Char('Z'), CTRL);
+        "Hell▯o▮ World", "world wide"
+    );
+
+    edit_test!(
+        test_replace_all_in_selection:
+        "He▯llo World", "world", "wor▮ld wide";
+        Key(Char('H'), CTRL), Key(Char('o'), 0), Key(Enter, 0), Key(Char('0'), 0), Key(Char('a'), ALT);
+        "Hell0▮ W0rld", "w0rld", "w0rld wide"
+    );
+
+    fn replace_with_line_break(lines: Vec<&str>, events: &[Event]) -> EditorState {
+        let mut state = state(lines);
+        for event in [Key(Char('H'), CTRL), Key(Char(','), 0), Key(Enter, 0)] {
+            process_event(&event, &mut state);
+        }
+        state.search.replace_with = s!["\n"];
+        events.iter().for_each(|event| { process_event(event, &mut state); });
+        state
+    }
+
+    #[test]
+    fn test_replace_all_multi_line() {
+        let state = replace_with_line_break(vec!["▮a,b", "c,d"], &[Key(Char('a'), ALT)]);
+        assert_eq!(state.content.to_string(), "a\nb\nc\nd");
+    }
+
+    #[test]
+    fn test_replace_next_multi_line_in_selection() {
+        let state = replace_with_line_break(vec!["▯a,b", "c,d▮", "e,f"], &[Key(Enter, 0), Key(Enter, 0), Key(Enter, 0)]);
+        assert_eq!(state.content.to_string(), "a\nb\nc\nd\ne,f");
+    }
+}
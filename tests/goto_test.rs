@@ -0,0 +1,46 @@
+#[macro_use]
+#[path ="./edit_test_parse.rs"]
+mod edit_test_parse;
+
+
+mod goto_test {
+    use super::edit_test_parse::{assert, state};
+
+    use picow::editor::events::process_event;
+    use picow::terminal::events::{Event::{Key, Paste}, KeyCode::*, ALT, CTRL};
+
+    edit_test!(
+        test_go_to_line_and_column:
+        "▮Hello", "Amazing", "World";
+        Key(Char('G'), CTRL), Paste(s!["2:4"]), Key(Enter, 0);
+        "Hello", "Ama▮zing", "World"
+    );
+
+    edit_test!(
+        test_go_to_relative:
+        "Hello", "Ama▮zing", "World";
+        Key(Char('G'), CTRL), Paste(s!["-1"]), Key(Enter, 0);
+        "▮Hello", "Amazing", "World"
+    );
+
+    edit_test!(
+        test_go_to_clamps_column:
+        "▮Hello", "Amazing", "World";
+        Key(Char('G'), CTRL), Paste(s!["3:99"]), Key(Enter, 0);
+        "Hello", "Amazing", "World▮"
+    );
+
+    edit_test!(
+        test_go_to_invalid_keeps_prompt:
+        "▮Hello", "Amazing", "World";
+        Key(Char('G'), CTRL), Paste(s!["x"]), Key(Enter, 0), Key(Char('a'), 0);
+        "▮Hello", "Amazing", "World"
+    );
+
+    edit_test!(
+        test_jump_back_and_forward:
+        "He▮llo", "Amazing", "World";
+        Key(Char('G'), CTRL), Paste(s!["3"]), Key(Enter, 0), Key(Left, ALT), Key(Right, ALT), Key(Left, ALT);
+        "He▮llo", "Amazing", "World"
+    );
+}
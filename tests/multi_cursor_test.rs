@@ -0,0 +1,100 @@
+#[macro_use]
+#[path ="./edit_test_parse.rs"]
+mod edit_test_parse;
+
+
+mod multi_cursor_test {
+    use super::edit_test_parse::{assert, state};
+
+    use picow::editor::{events::process_event, pos::PosInDocument, state::EditorState};
+    use picow::terminal::events::{Event::{self, Key, Mouse, Paste}, KeyCode::*, Mouse::Button, MouseButton, MouseEvent::*, ALT, CTRL, SHIFT};
+
+    fn carets(state: &EditorState) -> Vec<PosInDocument> {
+        state.carets().iter().map(|caret| caret.cursor.pos()).collect()
+    }
+
+    fn process(state: &mut EditorState, events: Vec<Event>) {
+        events.iter().for_each(|event| { process_event(event, state); });
+    }
+
+    edit_test!(
+        test_type_at_every_cursor:
+        "▮Hello", "World", "Again";
+        Key(Down, CTRL | ALT), Key(Down, CTRL | ALT), Key(Char('x'), 0);
+        "x▮Hello", "xWorld", "xAgain"
+    );
+
+    edit_test!(
+        test_backspace_at_every_cursor:
+        "Hel▮lo", "World";
+        Key(Down, CTRL | ALT), Key(Backspace, 0), Key(Backspace, 0);
+        "H▮lo", "Wld"
+    );
+
+    edit_test!(
+        test_paste_at_every_cursor:
+        "Hello", "Wor▮ld";
+        Key(Up, CTRL | ALT), Paste(s!["\n"]);
+        "Hel", "lo", "Wor", "▮ld"
+    );
+
+    edit_test!(
+        test_multi_cursor_edit_single_undo:
+        "▮Hello", "World";
+        Key(Down, CTRL | ALT), Key(Char('x'), 0), Key(Char('Z'), CTRL);
+        "▮Hello", "World"
+    );
+
+    edit_test!(
+        test_add_next_occurrence:
+        "f▮oo bar foo";
+        Key(Char('n'), ALT), Key(Char('n'), ALT), Key(Char('x'), 0);
+        "x bar x▮"
+    );
+
+    edit_test!(
+        test_column_selection_delete:
+        "He▮llo", "World", "Again";
+        Key(Down, ALT | SHIFT), Key(Down, ALT | SHIFT), Key(Right, ALT | SHIFT), Key(Right, ALT | SHIFT), Key(Delete, 0);
+        "Heo", "Wod", "Ag▮n"
+    );
+
+    edit_test!(
+        test_column_selection_mouse:
+        "▮Hello", "World";
+        Mouse(Button(MouseButton::Left, Press, 2, 1), ALT), Mouse(Button(MouseButton::Left, Drag, 4, 2), ALT), Key(Delete, 0);
+        "Hlo", "W▮ld"
+    );
+
+    #[test]
+    fn add_caret_keeps_column() {
+        let mut state = state(vec!["Hel▮lo", "Hi", "World"]);
+        process(&mut state, vec![Key(Down, CTRL | ALT), Key(Down, CTRL | ALT)]);
+        assert_eq!(carets(&state), vec![(1, 2), (2, 3)]);
+    }
+
+    #[test]
+    fn carets_move_together() {
+        let mut state = state(vec!["▮Hello", "World"]);
+        process(&mut state, vec![Key(Down, CTRL | ALT), Key(Right, SHIFT), Key(Right, SHIFT)]);
+
+        assert(&state, vec!["▯He▮llo", "World"]);
+        assert_eq!(state.carets()[0].selection(), Some(((1, 0), (1, 2))));
+    }
+
+    #[test]
+    fn esc_clears_carets() {
+        let mut state = state(vec!["▮Hello", "World"]);
+        process(&mut state, vec![Key(Down, CTRL | ALT), Key(Esc, 0), Key(Char('x'), 0)]);
+
+        assert(&state, vec!["x▮Hello", "World"]);
+        assert!(carets(&state).is_empty());
+    }
+
+    #[test]
+    fn carets_at_same_position_merge() {
+        let mut state = state(vec!["Hello", "W▮orld"]);
+        process(&mut state, vec![Key(Up, CTRL | ALT), Key(Home, CTRL)]);
+        assert!(carets(&state).is_empty());
+    }
+}
@@ -7,7 +7,7 @@ mod selection_test {
     use super::edit_test_parse::{assert, state};
 
     use picow::editor::events::process_event;
-    use picow::terminal::events::{Event::{Key, Paste}, KeyCode::*, CTRL};
+    use picow::terminal::events::{Event::{Key, Paste}, KeyCode::*, CTRL, SHIFT};
 
     edit_test!(
         test_selection_delete:
@@ -43,7 +43,7 @@ mod selection_test {
         "He▯llo Kitt▮y";
         Key(Char('i'), 0),
         Key(Char('Z'), CTRL);
-        "Hello Kitt▮y"
+        "He▯llo Kitt▮y"
     );
 
     edit_test!(
@@ -58,6 +58,45 @@ mod selection_test {
         "He▯llo Kitt▮y";
         Paste("llo World".to_string()),
         Key(Char('Z'), CTRL);
-        "Hello Kitt▮y"
+        "He▯llo Kitt▮y"
+    );
+
+    edit_test!(
+        test_selection_delete_undo:
+        "He▮llo Kitt▯y";
+        Key(Delete, 0),
+        Key(Char('Z'), CTRL);
+        "He▮llo Kitt▯y"
+    );
+
+    edit_test!(
+        test_selection_typed_over_redo:
+        "He▯llo Kitt▮y";
+        Key(Char('a'), 0),
+        Key(Char('Z'), CTRL),
+        Key(Char('Y'), CTRL);
+        "Hea▮y"
+    );
+
+    edit_test!(
+        test_select_word_right:
+        "▮Hello wonderful world";
+        Key(Right, CTRL | SHIFT),
+        Key(Right, CTRL | SHIFT);
+        "▯Hello wonderful ▮world"
+    );
+
+    edit_test!(
+        test_select_to_document_end:
+        "He▮llo", "World";
+        Key(End, CTRL | SHIFT);
+        "He▯llo", "World▮"
+    );
+
+    edit_test!(
+        test_select_to_document_start:
+        "Hello", "Wo▮rld";
+        Key(Home, CTRL | SHIFT);
+        "▮Hello", "Wo▯rld"
     );
 }